@@ -1,4 +1,5 @@
 use std::fmt;
+use std::path::PathBuf;
 use std::error::Error;
 
 #[derive(Debug)]
@@ -6,9 +7,15 @@ use std::error::Error;
 pub enum ClinerError {
     Io(std::io::Error),
     Serde(serde_json::Error),
+    Yaml(serde_yaml::Error),
     Parse(String),
     MissingField(String),
     InvalidFormat(String),
+    Config(String),
+    Git(String),
+    OutOfDate(String),
+    MergeConflict(String),
+    BudgetExceeded { output: PathBuf, tokens: usize, budget: usize, trimmed: bool },
 }
 
 impl Error for ClinerError {
@@ -16,6 +23,7 @@ impl Error for ClinerError {
         match self {
             ClinerError::Io(err) => Some(err),
             ClinerError::Serde(err) => Some(err),
+            ClinerError::Yaml(err) => Some(err),
             _ => None,
         }
     }
@@ -26,9 +34,20 @@ impl fmt::Display for ClinerError {
         match self {
             ClinerError::Io(err) => write!(f, "I/O Error: {}", err),
             ClinerError::Serde(err) => write!(f, "JSON Serialization Error: {}", err),
+            ClinerError::Yaml(err) => write!(f, "YAML Serialization Error: {}", err),
             ClinerError::Parse(msg) => write!(f, "Parse Error: {}", msg),
             ClinerError::MissingField(field) => write!(f, "Missing Required Field: {}", field),
             ClinerError::InvalidFormat(msg) => write!(f, "Invalid Format: {}", msg),
+            ClinerError::Config(msg) => write!(f, "Config Error: {}", msg),
+            ClinerError::Git(msg) => write!(f, "Git Error: {}", msg),
+            ClinerError::OutOfDate(msg) => write!(f, "{}", msg),
+            ClinerError::MergeConflict(msg) => write!(f, "{}", msg),
+            ClinerError::BudgetExceeded { output, tokens, budget, trimmed: false } => {
+                write!(f, "{} is about {} tokens, over the budget of {}; see `cliner stats`", output.display(), tokens, budget)
+            },
+            ClinerError::BudgetExceeded { output, tokens, budget, trimmed: true } => {
+                write!(f, "Trimming could not fit {} into the budget of {} tokens: it is still about {} tokens with every rule left out", output.display(), budget, tokens)
+            },
         }
     }
 }
@@ -45,9 +64,16 @@ impl From<serde_json::Error> for ClinerError {
     }
 }
 
+impl From<serde_yaml::Error> for ClinerError {
+    fn from(err: serde_yaml::Error) -> Self {
+        ClinerError::Yaml(err)
+    }
+}
+
 pub type Result<T> = std::result::Result<T, ClinerError>;
 
 #[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
 mod tests {
     use super::*;
     use std::io;
@@ -78,14 +104,38 @@ mod tests {
         }
     }
     
+    #[test]
+    fn test_from_yaml_error() {
+        let parse_result = serde_yaml::from_str::<serde_json::Value>("key: [unclosed");
+        assert!(parse_result.is_err());
+        
+        let cliner_error = ClinerError::from(parse_result.unwrap_err());
+        
+        match cliner_error {
+            ClinerError::Yaml(_) => assert!(true),
+            _ => panic!("Expected ClinerError::Yaml variant"),
+        }
+    }
+    
     #[test]
     fn test_display() {
         let parse_error = ClinerError::Parse("parse error".to_string());
         let missing_field = ClinerError::MissingField("name".to_string());
         let invalid_format = ClinerError::InvalidFormat("invalid".to_string());
+        let config = ClinerError::Config("bad key".to_string());
+        let out_of_date = ClinerError::OutOfDate("1 generated file(s) out of date".to_string());
+        let budget = ClinerError::BudgetExceeded { output: PathBuf::from(".clinerules"), tokens: 10, budget: 5, trimmed: false };
         
         assert!(format!("{}", parse_error).contains("Parse Error: parse error"));
         assert!(format!("{}", missing_field).contains("Missing Required Field: name"));
         assert!(format!("{}", invalid_format).contains("Invalid Format: invalid"));
+        assert!(format!("{}", config).contains("Config Error: bad key"));
+        assert_eq!(format!("{}", out_of_date), "1 generated file(s) out of date");
+        let trimmed = ClinerError::BudgetExceeded { output: PathBuf::from(".clinerules"), tokens: 8, budget: 5, trimmed: true };
+        assert_eq!(format!("{}", budget), ".clinerules is about 10 tokens, over the budget of 5; see `cliner stats`");
+        assert_eq!(
+            format!("{}", trimmed),
+            "Trimming could not fit .clinerules into the budget of 5 tokens: it is still about 8 tokens with every rule left out"
+        );
     }
 }
@@ -1,17 +1,39 @@
+use std::cmp::Reverse;
+use std::fs;
+use std::path::Path;
+use serde_json::Value;
 use crate::error::{ClinerError, Result};
-use crate::models::ClinePaths;
-use crate::processors::FileProcessor;
+use crate::models::{BudgetAction, ClinePaths, GenerateOptions, OutputTarget, SourceDir, SourceFile, SourceKind};
+use crate::processors::{FileProcessor, ModeMerger, ProjectContext, SourceRenderer};
 use crate::generators::OutputGenerator;
 
+/// The rendered rules for `.clinerules`, with whether each one fits the budget.
+struct RenderedRules {
+    rendered: Vec<(SourceFile, String)>,
+    kept: Vec<bool>,
+    context: Option<String>,
+}
+
+impl RenderedRules {
+    fn kept_contents(&self) -> impl Iterator<Item = &str> {
+        self.rendered.iter().zip(&self.kept).filter(|(_, keep)| **keep).map(|((_, content), _)| content.as_str())
+    }
+    
+    fn dropped(&self) -> impl Iterator<Item = &(SourceFile, String)> {
+        self.rendered.iter().zip(&self.kept).filter(|(_, keep)| !**keep).map(|(rule, _)| rule)
+    }
+}
+
 pub struct ClinerGenerator {
     paths: ClinePaths,
+    options: GenerateOptions,
+    renderer: SourceRenderer,
 }
 
 impl ClinerGenerator {
-    pub fn new() -> Self {
-        Self {
-            paths: ClinePaths::new(),
-        }
+    pub fn new(paths: ClinePaths, options: GenerateOptions) -> Self {
+        let renderer = SourceRenderer::new(&paths, &options);
+        Self { paths, options, renderer }
     }
     
     pub fn validate_cline_exists(&self) -> Result<()> {
@@ -19,30 +41,332 @@ impl ClinerGenerator {
             return Ok(());
         }
         
-        eprintln!("Error: .cline directory not found");
         Err(ClinerError::InvalidFormat(".cline directory not found".to_string()))
     }
     
     pub fn generate_roomodes(&self) -> Result<()> {
-        if !self.paths.modes_exists() {
-            println!("modes directory not found, skipping .roomodes generation");
+        let Some(modes_json_values) = self.build_modes(true)? else {
             return Ok(());
+        };
+        
+        let roomodes_path = &self.paths.roomodes;
+        let success_message = format!("Generated {}", roomodes_path.display());
+        let header = self.options.selection_header();
+        OutputGenerator::write_modes_if_not_empty(
+            modes_json_values,
+            roomodes_path,
+            self.options.format,
+            header.as_deref(),
+            &success_message,
+        )
+    }
+    
+    /// The modes for `.roomodes`, or `None` if it isn't generated. With `report`, says why
+    /// it was skipped and prints the merge summary.
+    fn build_modes(&self, report: bool) -> Result<Option<Vec<Value>>> {
+        if !self.options.is_target_enabled(OutputTarget::Roomodes) {
+            return Ok(None);
+        }
+        
+        if !self.paths.any_source_dir_exists(SourceKind::Modes) {
+            if report {
+                println!("modes directory not found, skipping .roomodes generation");
+            }
+            return Ok(None);
         }
     
-        let sorted_mode_entries = FileProcessor::collect_sorted_entries(&self.paths.modes)?;
-        let modes_json_values = FileProcessor::convert_entries_to_json(sorted_mode_entries);
-        OutputGenerator::write_json_if_not_empty(modes_json_values, ".roomodes", "Generated .roomodes")
+        let mode_dirs = self.paths.source_dirs(SourceKind::Modes);
+        let sorted_mode_entries = self.collect_sources(&mode_dirs, SourceKind::Modes)?;
+        let mut modes_json_values = FileProcessor::convert_entries_to_json(sorted_mode_entries, &self.renderer, self.options.strict)?;
+        self.adjust_groups(&mut modes_json_values);
+        // Keys are written sorted, as without `preserve_order`; only merged modes keep the order
+        // they have in the existing file.
+        for mode in &mut modes_json_values {
+            mode.sort_all_objects();
+        }
+        if self.options.merge {
+            modes_json_values = Self::merge_with_existing(modes_json_values, &self.paths.roomodes, report)?;
+        }
+        
+        Ok(Some(modes_json_values))
+    }
+    
+    fn collect_sources(&self, source_dirs: &[SourceDir], kind: SourceKind) -> Result<Vec<SourceFile>> {
+        let selected = FileProcessor::select_sources(source_dirs, kind, &self.options)?;
+        if self.options.verbose {
+            for skipped in &selected.skipped {
+                println!("Skipped {}", skipped);
+            }
+        }
+        
+        Ok(selected.files)
+    }
+    
+    /// Applies the profile's `remove_groups` and `add_groups` to every generated mode.
+    fn adjust_groups(&self, modes_json_values: &mut [Value]) {
+        if self.options.remove_groups.is_empty() && self.options.add_groups.is_empty() {
+            return;
+        }
+        
+        for mode in modes_json_values.iter_mut() {
+            let Some(groups) = mode.get_mut("groups").and_then(Value::as_array_mut) else {
+                continue;
+            };
+            
+            // A group is either a name or `[name, options]`.
+            let group_name = |group: &Value| match group {
+                Value::Array(items) => items.first().and_then(Value::as_str).map(String::from),
+                other => other.as_str().map(String::from),
+            };
+            groups.retain(|group| group_name(group).is_none_or(|name| !self.options.remove_groups.contains(&name)));
+            
+            for added in &self.options.add_groups {
+                if !groups.iter().any(|group| group_name(group).as_ref() == Some(added)) {
+                    groups.push(Value::String(added.clone()));
+                }
+            }
+        }
+    }
+    
+    fn merge_with_existing(mut modes_json_values: Vec<Value>, roomodes_path: &Path, report: bool) -> Result<Vec<Value>> {
+        ModeMerger::mark_managed(&mut modes_json_values);
+        let existing_modes = ModeMerger::read_existing_modes(roomodes_path)?;
+        let (merged_modes, summary) = ModeMerger::merge(existing_modes, modes_json_values);
+        if !report {
+            return Ok(merged_modes);
+        }
+        
+        println!(
+            "Merged {}: {} updated, {} added, {} removed, {} custom kept",
+            roomodes_path.display(),
+            summary.updated,
+            summary.added,
+            summary.removed,
+            summary.kept
+        );
+        Ok(merged_modes)
     }
     
     pub fn generate_clinerules(&self) -> Result<()> {
-        if !self.paths.rules_exists() {
-            println!("rules directory not found, skipping .clinerules generation");
+        let Some(concatenated_rules) = self.build_rules(true)? else {
             return Ok(());
+        };
+        
+        let clinerules_path = &self.paths.clinerules;
+        let success_message = format!("Generated {}", clinerules_path.display());
+        OutputGenerator::write_content_if_not_empty(concatenated_rules, clinerules_path, &success_message)
+    }
+    
+    /// The content of `.clinerules`, or `None` if it isn't generated.
+    fn build_rules(&self, report: bool) -> Result<Option<String>> {
+        let concatenated_rules = self.concatenate_rules(report)?;
+        if let Some(concatenated_rules) = &concatenated_rules {
+            self.check_budget(concatenated_rules)?;
         }
+        Ok(concatenated_rules)
+    }
     
-        let sorted_rule_entries = FileProcessor::collect_sorted_entries(&self.paths.rules)?;
-        let concatenated_rules = FileProcessor::concatenate_entries(sorted_rule_entries);
-        OutputGenerator::write_content_if_not_empty(concatenated_rules, ".clinerules", "Generated .clinerules")
+    fn concatenate_rules(&self, report: bool) -> Result<Option<String>> {
+        let Some(rules) = self.render_rule_set(report)? else {
+            return Ok(None);
+        };
+        
+        if report && let Some(budget) = self.trim_budget() {
+            let dropped: Vec<_> = rules.dropped().collect();
+            if !dropped.is_empty() {
+                println!("Left out of {} to fit the budget of {} tokens:", self.paths.clinerules.display(), budget);
+                for (source, content) in dropped {
+                    let priority = source.metadata.priority.unwrap_or(0);
+                    let count = self.options.tokens.count(content);
+                    println!("  {} (priority {}, ~{} tokens)", self.display_path(&source.path), priority, count);
+                }
+            }
+        }
+        
+        Ok(Some(self.join_rules(&rules)))
+    }
+    
+    /// Renders every rule once and, with `on_exceed = "trim"`, decides which ones fit the budget.
+    fn render_rule_set(&self, report: bool) -> Result<Option<RenderedRules>> {
+        let Some(sorted_rule_entries) = self.rule_sources(report)? else {
+            return Ok(None);
+        };
+        
+        let context = self.project_context()?;
+        let rendered = FileProcessor::render_rules(sorted_rule_entries, &self.renderer, self.options.strict)?;
+        let kept = match self.trim_budget() {
+            Some(budget) => self.trim_to_budget(&rendered, context.as_deref(), budget),
+            None => vec![true; rendered.len()],
+        };
+        Ok(Some(RenderedRules { rendered, kept, context }))
+    }
+    
+    /// The content of `.clinerules` made of the rules that were kept.
+    fn join_rules(&self, rules: &RenderedRules) -> String {
+        let concatenated_rules = FileProcessor::concatenate_rendered(rules.kept_contents(), &self.options.rule_separator);
+        self.wrap_rules(concatenated_rules, rules.context.as_deref())
+    }
+    
+    /// Adds the project context and the selection header around the concatenated rules.
+    fn wrap_rules(&self, mut concatenated_rules: String, context: Option<&str>) -> String {
+        if let Some(context) = context {
+            concatenated_rules.insert_str(0, &format!("{}{}", context, self.options.rule_separator));
+        }
+        if let Some(header) = self.options.selection_header()
+            && !concatenated_rules.is_empty()
+        {
+            concatenated_rules.insert_str(0, &format!("<!-- {} -->\n\n", header));
+        }
+        concatenated_rules
+    }
+    
+    fn trim_budget(&self) -> Option<usize> {
+        let tokens = &self.options.tokens;
+        tokens.budget.filter(|_| tokens.on_exceed == BudgetAction::Trim)
+    }
+    
+    /// Leaves out the lowest-priority rules until `.clinerules` fits in `budget`, and returns
+    /// whether each rule is kept. A rule without a priority counts as 0, and of rules with the
+    /// same priority the later one goes first. The project context is always kept.
+    fn trim_to_budget(&self, rendered: &[(SourceFile, String)], context: Option<&str>, budget: usize) -> Vec<bool> {
+        let measure = |kept: &[bool]| {
+            let contents = rendered.iter().zip(kept).filter(|(_, keep)| **keep).map(|((_, content), _)| content.as_str());
+            let concatenated_rules = FileProcessor::concatenate_rendered(contents, &self.options.rule_separator);
+            self.options.tokens.count(&self.wrap_rules(concatenated_rules, context))
+        };
+        
+        let mut drop_order: Vec<usize> = (0..rendered.len()).collect();
+        drop_order.sort_by_key(|&index| (rendered[index].0.metadata.priority.unwrap_or(0), Reverse(index)));
+        
+        let mut kept = vec![true; rendered.len()];
+        for index in drop_order {
+            if measure(&kept) <= budget {
+                break;
+            }
+            kept[index] = false;
+        }
+        kept
+    }
+    
+    /// The rules that go into `.clinerules`, or `None` if it isn't generated.
+    fn rule_sources(&self, report: bool) -> Result<Option<Vec<SourceFile>>> {
+        if !self.options.is_target_enabled(OutputTarget::Clinerules) {
+            return Ok(None);
+        }
+        
+        if !self.paths.any_source_dir_exists(SourceKind::Rules) && self.options.context_depth.is_none() {
+            if report {
+                println!("rules directory not found, skipping .clinerules generation");
+            }
+            return Ok(None);
+        }
+        
+        let rule_dirs = self.paths.source_dirs(SourceKind::Rules);
+        Ok(Some(self.collect_sources(&rule_dirs, SourceKind::Rules)?))
+    }
+    
+    fn project_context(&self) -> Result<Option<String>> {
+        let Some(depth) = self.options.context_depth else {
+            return Ok(None);
+        };
+        Ok(Some(ProjectContext::render(&self.paths.root, &self.paths.context_dir(), depth)?))
+    }
+    
+    /// Warns about or rejects a `.clinerules` still over `tokens.budget`.
+    fn check_budget(&self, concatenated_rules: &str) -> Result<()> {
+        let tokens = &self.options.tokens;
+        let Some(budget) = tokens.budget else {
+            return Ok(());
+        };
+        
+        let count = tokens.count(concatenated_rules);
+        if count <= budget {
+            return Ok(());
+        }
+        
+        let error = ClinerError::BudgetExceeded {
+            output: self.paths.clinerules.clone(),
+            tokens: count,
+            budget,
+            trimmed: tokens.on_exceed == BudgetAction::Trim,
+        };
+        if tokens.on_exceed == BudgetAction::Warn {
+            eprintln!("Warning: {}", error);
+            return Ok(());
+        }
+        Err(error)
+    }
+    
+    /// Approximate token counts for every rule and mode, and for each output.
+    pub fn render_stats(&self) -> Result<String> {
+        self.validate_cline_exists()?;
+        let tokens = &self.options.tokens;
+        let mut report = String::new();
+        let mut total = 0;
+        
+        if let Some(rules) = self.render_rule_set(false)? {
+            let mut rows = Vec::new();
+            if let Some(context) = &rules.context {
+                rows.push((tokens.count(context), "(project context)".to_string()));
+            }
+            for ((source, content), keep) in rules.rendered.iter().zip(&rules.kept) {
+                let mut name = self.display_path(&source.path);
+                if let Some(title) = &source.metadata.title {
+                    name.push_str(&format!(" - {}", title));
+                }
+                if !keep {
+                    name.push_str(" (left out to fit the budget)");
+                }
+                rows.push((tokens.count(content), name));
+            }
+            
+            let count = tokens.count(&self.join_rules(&rules));
+            total += count;
+            let budget = match tokens.budget {
+                Some(budget) if count > budget => format!(", over the budget of {}", budget),
+                Some(budget) => format!(", budget {}", budget),
+                None => String::new(),
+            };
+            Self::push_stats_section(&mut report, &self.paths.clinerules, count, &budget, &rows);
+        }
+        
+        if let Some(modes_json_values) = self.build_modes(false)? {
+            let mut rows = Vec::new();
+            for mode in &modes_json_values {
+                let slug = mode.get("slug").and_then(Value::as_str).unwrap_or("(unnamed)");
+                rows.push((tokens.count(&serde_json::to_string_pretty(mode)?), slug.to_string()));
+            }
+            
+            let header = self.options.selection_header();
+            let content = OutputGenerator::render_modes(modes_json_values, self.options.format, header.as_deref())?;
+            let count = tokens.count(&content);
+            total += count;
+            Self::push_stats_section(&mut report, &self.paths.roomodes, count, "", &rows);
+        }
+        
+        report.push_str(&format!("Total: ~{} tokens\n", total));
+        Ok(report)
+    }
+    
+    pub fn run_stats(&self) -> Result<()> {
+        print!("{}", self.render_stats()?);
+        Ok(())
+    }
+    
+    fn push_stats_section(report: &mut String, output_path: &Path, count: usize, budget: &str, rows: &[(usize, String)]) {
+        report.push_str(&format!("{} (~{} tokens{}):\n", output_path.display(), count, budget));
+        if rows.is_empty() {
+            report.push_str("  (none)\n");
+        }
+        for (count, name) in rows {
+            report.push_str(&format!("  {:>7}  {}\n", count, name));
+        }
+        report.push('\n');
+    }
+    
+    fn display_path(&self, path: &Path) -> String {
+        let relative_path = path.strip_prefix(&self.paths.root).unwrap_or(path);
+        relative_path.display().to_string()
     }
     
     pub fn run_generate(&self) -> Result<()> {
@@ -59,12 +383,67 @@ impl ClinerGenerator {
         Ok(())
     }
     
+    /// Regenerates every output in memory and compares it with the file on disk, so stale
+    /// outputs, for example after an embedded file changed, fail the check.
+    pub fn run_check(&self) -> Result<()> {
+        self.validate_cline_exists()?;
+        
+        // An output of an enabled target that wouldn't be generated any more is stale if it's
+        // still on disk; outputs of disabled targets aren't cliner's to check.
+        let modes = self.build_modes(false)?.filter(|modes_json_values| !modes_json_values.is_empty());
+        let expected_modes = match modes {
+            Some(modes_json_values) => {
+                let header = self.options.selection_header();
+                Some(OutputGenerator::render_modes(modes_json_values, self.options.format, header.as_deref())?)
+            },
+            None => None,
+        };
+        let expected_rules = self.build_rules(false)?.filter(|concatenated_rules| !concatenated_rules.is_empty());
+        let expected_outputs = [
+            (OutputTarget::Roomodes, &self.paths.roomodes, expected_modes),
+            (OutputTarget::Clinerules, &self.paths.clinerules, expected_rules),
+        ];
+        
+        let mut stale_count = 0;
+        for (target, path, expected) in expected_outputs {
+            if !self.options.is_target_enabled(target) {
+                continue;
+            }
+            
+            match (fs::read_to_string(path), expected) {
+                (Ok(existing), Some(expected)) if existing == expected => println!("Up to date: {}", path.display()),
+                (Ok(_), Some(_)) => {
+                    stale_count += 1;
+                    println!("Out of date: {}", path.display());
+                },
+                (Err(_), Some(_)) => {
+                    stale_count += 1;
+                    println!("Missing: {}", path.display());
+                },
+                (Ok(_), None) => {
+                    stale_count += 1;
+                    println!("No longer generated: {}", path.display());
+                },
+                (Err(_), None) => {},
+            }
+        }
+        
+        if stale_count > 0 {
+            return Err(ClinerError::OutOfDate(format!(
+                "{} generated file(s) out of date; run `cliner generate` and remove files no longer generated",
+                stale_count
+            )));
+        }
+        Ok(())
+    }
+    
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs::{self, create_dir_all, File};
+    use crate::models::TagExpression;
+    use std::fs::{create_dir_all, File};
     use std::io::Write;
     use tempfile::TempDir;
     use std::path::{Path, PathBuf};
@@ -103,61 +482,8 @@ mod tests {
         Ok(())
     }
 
-    struct TestClinerGenerator {
-        paths: ClinePaths,
-    }
-    impl TestClinerGenerator {
-        fn new(base_dir: &Path) -> Self {
-            let base = base_dir.join(".cline");
-            let modes = base.join("modes");
-            let rules = base.join("rules");
-            
-            Self {
-                paths: ClinePaths { base, modes, rules }
-            }
-        }
-        
-        fn validate_cline_exists(&self) -> Result<()> {
-            if self.paths.base_exists() {
-                return Ok(());
-            }
-            eprintln!("Error: .cline directory not found");
-            Err(ClinerError::InvalidFormat(".cline directory not found".to_string()))
-        }
-        
-        fn generate_roomodes_with_path(&self, output_path: &str) -> Result<()> {
-            if !self.paths.modes_exists() {
-                println!("modes directory not found, skipping .roomodes generation");
-                return Ok(());
-            }
-            
-            let sorted_mode_entries = FileProcessor::collect_sorted_entries(&self.paths.modes)?;
-            let modes_json_values = FileProcessor::convert_entries_to_json(sorted_mode_entries);
-            OutputGenerator::write_json_if_not_empty(modes_json_values, output_path, "Generated .roomodes")
-        }
-        
-        fn generate_clinerules_with_path(&self, output_path: &str) -> Result<()> {
-            if !self.paths.rules_exists() {
-                println!("rules directory not found, skipping .clinerules generation");
-                return Ok(());
-            }
-            
-            let sorted_rule_entries = FileProcessor::collect_sorted_entries(&self.paths.rules)?;
-            let concatenated_rules = FileProcessor::concatenate_entries(sorted_rule_entries);
-            OutputGenerator::write_content_if_not_empty(concatenated_rules, output_path, "Generated .clinerules")
-        }
-        
-        fn run_with_paths(&self, roomodes_path: &str, clinerules_path: &str) -> Result<()> {
-            self.validate_cline_exists()?;
-            
-            let roomodes_result = self.generate_roomodes_with_path(roomodes_path);
-            let clinerules_result = self.generate_clinerules_with_path(clinerules_path);
-            
-            roomodes_result?;
-            clinerules_result?;
-            
-            Ok(())
-        }
+    fn create_generator(temp_dir: &TempDir) -> ClinerGenerator {
+        ClinerGenerator::new(ClinePaths::from_root(temp_dir.path()), GenerateOptions::default())
     }
     
     #[test]
@@ -165,7 +491,7 @@ mod tests {
         let temp_dir = TempDir::new().unwrap();
         let (_modes_dir, _rules_dir) = create_test_cline_directory(&temp_dir).unwrap();
         
-        let generator = TestClinerGenerator::new(temp_dir.path());
+        let generator = create_generator(&temp_dir);
         let result = generator.validate_cline_exists();
         
         assert!(result.is_ok());
@@ -176,10 +502,9 @@ mod tests {
         let temp_dir = TempDir::new().unwrap();
         let (_modes_dir, _) = create_test_cline_directory(&temp_dir).unwrap();
         
-        let generator = TestClinerGenerator::new(temp_dir.path());
+        let generator = create_generator(&temp_dir);
         
-        let roomodes_path = temp_dir.path().join(".roomodes").to_str().unwrap().to_string();
-        let result = generator.generate_roomodes_with_path(&roomodes_path);
+        let result = generator.generate_roomodes();
         
         assert!(result.is_ok());
         
@@ -192,15 +517,132 @@ mod tests {
         cleanup_generated_files(temp_dir.path()).unwrap();
     }
 
+    #[test]
+    fn test_generate_roomodes_writes_sorted_keys() {
+        let temp_dir = TempDir::new().unwrap();
+        create_test_cline_directory(&temp_dir).unwrap();
+        
+        create_generator(&temp_dir).generate_roomodes().unwrap();
+        
+        let content = fs::read_to_string(temp_dir.path().join(".roomodes")).unwrap();
+        let keys: Vec<&str> = content.lines().filter(|line| line.starts_with("      \"")).map(|line| line.trim().split('"').nth(1).unwrap()).collect();
+        let mut sorted_keys = keys.clone();
+        sorted_keys.sort();
+        assert!(!keys.is_empty());
+        assert_eq!(keys, sorted_keys);
+    }
+    
+    #[test]
+    fn test_generate_roomodes_adjusts_groups() {
+        let temp_dir = TempDir::new().unwrap();
+        create_test_cline_directory(&temp_dir).unwrap();
+        let options = GenerateOptions {
+            remove_groups: vec!["edit".to_string()],
+            add_groups: vec!["browser".to_string(), "read".to_string()],
+            ..GenerateOptions::default()
+        };
+        
+        ClinerGenerator::new(ClinePaths::from_root(temp_dir.path()), options).generate_roomodes().unwrap();
+        
+        let roomodes: Value = serde_json::from_str(&fs::read_to_string(temp_dir.path().join(".roomodes")).unwrap()).unwrap();
+        assert_eq!(roomodes["customModes"][0]["groups"], serde_json::json!(["read", "browser"]));
+    }
+    
+    #[test]
+    fn test_generate_clinerules_records_selection() {
+        let temp_dir = TempDir::new().unwrap();
+        create_test_cline_directory(&temp_dir).unwrap();
+        let options = GenerateOptions { select: Some(TagExpression::parse("!legacy").unwrap()), ..GenerateOptions::default() };
+        
+        ClinerGenerator::new(ClinePaths::from_root(temp_dir.path()), options).generate_clinerules().unwrap();
+        
+        let content = fs::read_to_string(temp_dir.path().join(".clinerules")).unwrap();
+        assert!(content.starts_with("<!-- Generated by cliner with select: !legacy -->\n\n# Test Rule"));
+    }
+    
+    #[test]
+    fn test_generate_resolves_includes_in_modes_and_rules() {
+        let temp_dir = TempDir::new().unwrap();
+        let (modes_dir, rules_dir) = create_test_cline_directory(&temp_dir).unwrap();
+        let partials_dir = temp_dir.path().join(".cline/partials");
+        create_dir_all(&partials_dir).unwrap();
+        fs::write(partials_dir.join("style.md"), "Follow the style guide.\n").unwrap();
+        fs::write(modes_dir.join("test_mode.md"), "name: TestMode\n---\nRole\n<!-- @include ../partials/style.md -->\n").unwrap();
+        fs::write(rules_dir.join("test_rule.md"), "<!-- @include ../partials/style.md -->\n").unwrap();
+        
+        create_generator(&temp_dir).run_generate().unwrap();
+        
+        let roomodes = fs::read_to_string(temp_dir.path().join(".roomodes")).unwrap();
+        assert!(roomodes.contains("Role\\nFollow the style guide."));
+        let clinerules = fs::read_to_string(temp_dir.path().join(".clinerules")).unwrap();
+        assert!(clinerules.starts_with("Follow the style guide.\n"));
+    }
+    
+    #[test]
+    fn test_check_detects_changed_excerpt() {
+        let temp_dir = TempDir::new().unwrap();
+        let (_, rules_dir) = create_test_cline_directory(&temp_dir).unwrap();
+        fs::write(temp_dir.path().join("schema.graphql"), "type Query\n").unwrap();
+        fs::write(rules_dir.join("test_rule.md"), "Schema:\n{{file:schema.graphql#L1}}\n").unwrap();
+        let generator = create_generator(&temp_dir);
+        
+        assert!(generator.run_check().is_err());
+        generator.run_generate().unwrap();
+        generator.run_check().unwrap();
+        
+        fs::write(temp_dir.path().join("schema.graphql"), "type Mutation\n").unwrap();
+        let message = generator.run_check().unwrap_err().to_string();
+        assert!(message.contains("1 generated file(s) out of date"));
+    }
+    
+    #[test]
+    fn test_check_reports_outputs_no_longer_generated() {
+        let temp_dir = TempDir::new().unwrap();
+        let (modes_dir, _) = create_test_cline_directory(&temp_dir).unwrap();
+        let generator = create_generator(&temp_dir);
+        generator.run_generate().unwrap();
+        
+        fs::remove_dir_all(modes_dir).unwrap();
+        let error = generator.run_check().unwrap_err();
+        assert!(matches!(error, ClinerError::OutOfDate(_)));
+        assert!(error.to_string().starts_with("1 generated file(s) out of date"));
+        
+        let options = GenerateOptions { targets: vec![OutputTarget::Clinerules], ..GenerateOptions::default() };
+        ClinerGenerator::new(ClinePaths::from_root(temp_dir.path()), options).run_check().unwrap();
+    }
+    
+    #[cfg(unix)]
+    #[test]
+    fn test_check_uses_cached_command_output() {
+        let temp_dir = TempDir::new().unwrap();
+        let (_, rules_dir) = create_test_cline_directory(&temp_dir).unwrap();
+        fs::write(rules_dir.join("test_rule.md"), "Members:\n{{command:members}}\n").unwrap();
+        let options = |script: &str, cached_commands: bool| {
+            let command = crate::models::CommandConfig {
+                run: vec!["sh".to_string(), "-c".to_string(), script.to_string()],
+                timeout_secs: 5,
+                max_output_bytes: 1024,
+            };
+            GenerateOptions { commands: [("members".to_string(), command)].into(), cached_commands, ..GenerateOptions::default() }
+        };
+        
+        ClinerGenerator::new(ClinePaths::from_root(temp_dir.path()), options("echo core", false)).run_generate().unwrap();
+        assert_eq!(fs::read_to_string(temp_dir.path().join(".clinerules")).unwrap(), "Members:\ncore\n\n");
+        
+        let cached = ClinerGenerator::new(ClinePaths::from_root(temp_dir.path()), options("touch ran; echo changed", true));
+        cached.run_check().unwrap();
+        cached.render_stats().unwrap();
+        assert!(!temp_dir.path().join("ran").exists());
+    }
+    
     #[test]
     fn test_generate_clinerules() {
         let temp_dir = TempDir::new().unwrap();
         let (_, _rules_dir) = create_test_cline_directory(&temp_dir).unwrap();
         
-        let generator = TestClinerGenerator::new(temp_dir.path());
+        let generator = create_generator(&temp_dir);
         
-        let clinerules_path = temp_dir.path().join(".clinerules").to_str().unwrap().to_string();
-        let result = generator.generate_clinerules_with_path(&clinerules_path);
+        let result = generator.generate_clinerules();
         
         assert!(result.is_ok());
         
@@ -218,12 +660,9 @@ mod tests {
         let temp_dir = TempDir::new().unwrap();
         let (_modes_dir, _rules_dir) = create_test_cline_directory(&temp_dir).unwrap();
         
-        let generator = TestClinerGenerator::new(temp_dir.path());
+        let generator = create_generator(&temp_dir);
         
-        let roomodes_path = temp_dir.path().join(".roomodes").to_str().unwrap().to_string();
-        let clinerules_path = temp_dir.path().join(".clinerules").to_str().unwrap().to_string();
-        
-        let result = generator.run_with_paths(&roomodes_path, &clinerules_path);
+        let result = generator.run_generate();
         
         assert!(result.is_ok());
         
@@ -235,4 +674,146 @@ mod tests {
         
         cleanup_generated_files(temp_dir.path()).unwrap();
     }
+    
+    #[test]
+    fn test_merge_with_existing_keeps_custom_modes() {
+        let temp_dir = TempDir::new().unwrap();
+        let roomodes_path = temp_dir.path().join(".roomodes");
+        fs::write(&roomodes_path, r#"{"customModes": [{"slug": "ui-mode", "name": "UI Mode"}]}"#).unwrap();
+        
+        let generated = vec![serde_json::json!({"slug": "test-mode", "name": "TestMode"})];
+        let merged = ClinerGenerator::merge_with_existing(generated, &roomodes_path, true).unwrap();
+        
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0]["slug"], "ui-mode");
+        assert_eq!(merged[1]["slug"], "test-mode");
+        assert!(ModeMerger::is_managed(&merged[1]));
+    }
+    
+    #[test]
+    fn test_validate_cline_exists_when_missing() {
+        let temp_dir = TempDir::new().unwrap();
+        let generator = create_generator(&temp_dir);
+        
+        assert!(generator.validate_cline_exists().is_err());
+    }
+    
+    #[test]
+    fn test_run_generate_with_output_overrides() {
+        let temp_dir = TempDir::new().unwrap();
+        create_test_cline_directory(&temp_dir).unwrap();
+        
+        let mut paths = ClinePaths::from_root(temp_dir.path());
+        let roomodes_path = temp_dir.path().join("build").join("modes.json");
+        paths.set_output_path(OutputTarget::Roomodes, roomodes_path.clone());
+        
+        let generator = ClinerGenerator::new(paths, GenerateOptions::default());
+        let result = generator.run_generate();
+        
+        assert!(result.is_ok());
+        assert!(roomodes_path.exists());
+        assert!(!temp_dir.path().join(".roomodes").exists());
+        assert!(temp_dir.path().join(".clinerules").exists());
+    }
+    
+    #[test]
+    fn test_run_generate_only_enabled_targets() {
+        let temp_dir = TempDir::new().unwrap();
+        create_test_cline_directory(&temp_dir).unwrap();
+        
+        let options = GenerateOptions {
+            targets: vec![OutputTarget::Clinerules],
+            ..GenerateOptions::default()
+        };
+        let generator = ClinerGenerator::new(ClinePaths::from_root(temp_dir.path()), options);
+        
+        assert!(generator.run_generate().is_ok());
+        assert!(!temp_dir.path().join(".roomodes").exists());
+        assert!(temp_dir.path().join(".clinerules").exists());
+    }
+    
+    #[test]
+    fn test_project_context_comes_first() {
+        let temp_dir = TempDir::new().unwrap();
+        create_test_cline_directory(&temp_dir).unwrap();
+        fs::write(temp_dir.path().join("Cargo.toml"), "[package]\nname = \"demo\"\n").unwrap();
+        
+        let options = GenerateOptions { context_depth: Some(1), ..GenerateOptions::default() };
+        let generator = ClinerGenerator::new(ClinePaths::from_root(temp_dir.path()), options);
+        generator.generate_clinerules().unwrap();
+        
+        let content = fs::read_to_string(temp_dir.path().join(".clinerules")).unwrap();
+        assert!(content.starts_with("# Project Context\n\n## Language\n\n- Rust, crate `demo`\n"));
+        assert!(content.find("## Build").unwrap() < content.find("# Test Rule").unwrap());
+    }
+    
+    #[test]
+    fn test_render_stats() {
+        let temp_dir = TempDir::new().unwrap();
+        create_test_cline_directory(&temp_dir).unwrap();
+        
+        let report = create_generator(&temp_dir).render_stats().unwrap();
+        
+        assert!(report.contains(&format!("{} (~10 tokens):\n       10  .cline/rules/test_rule.md\n", temp_dir.path().join(".clinerules").display())));
+        assert!(report.contains("  testmode\n"));
+        assert!(report.contains("Total: ~"));
+    }
+    
+    #[test]
+    fn test_token_budget() {
+        let temp_dir = TempDir::new().unwrap();
+        create_test_cline_directory(&temp_dir).unwrap();
+        let generator = |on_exceed| {
+            let mut options = GenerateOptions::default();
+            options.tokens.budget = Some(5);
+            options.tokens.on_exceed = on_exceed;
+            ClinerGenerator::new(ClinePaths::from_root(temp_dir.path()), options)
+        };
+        
+        assert!(generator(BudgetAction::Warn).generate_clinerules().is_ok());
+        let error = generator(BudgetAction::Fail).run_check().unwrap_err();
+        assert!(matches!(error, ClinerError::BudgetExceeded { tokens: 10, budget: 5, trimmed: false, .. }));
+        assert!(error.to_string().contains("is about 10 tokens, over the budget of 5"));
+        assert!(generator(BudgetAction::Fail).render_stats().unwrap().contains("over the budget of 5"));
+    }
+    
+    #[test]
+    fn test_trim_to_budget_drops_lowest_priority_rules() {
+        let temp_dir = TempDir::new().unwrap();
+        let (_, rules_dir) = create_test_cline_directory(&temp_dir).unwrap();
+        fs::write(rules_dir.join("00_core.md"), "---\npriority: 10\n---\nCore rule that must stay.").unwrap();
+        fs::write(rules_dir.join("10_extra.md"), "---\npriority: 5\n---\nExtra detail that helps a little.").unwrap();
+        
+        let mut options = GenerateOptions::default();
+        options.tokens.budget = Some(20);
+        options.tokens.on_exceed = BudgetAction::Trim;
+        let generator = ClinerGenerator::new(ClinePaths::from_root(temp_dir.path()), options);
+        generator.run_generate().unwrap();
+        
+        let content = fs::read_to_string(temp_dir.path().join(".clinerules")).unwrap();
+        assert!(content.contains("Core rule"));
+        assert!(content.contains("Extra detail"));
+        assert!(!content.contains("Test Rule"));
+        assert!(generator.run_check().is_ok());
+        
+        let report = generator.render_stats().unwrap();
+        assert!(report.contains(".cline/rules/test_rule.md (left out to fit the budget)"));
+        assert!(!report.contains("10_extra.md (left out"));
+    }
+    
+    #[test]
+    fn test_trim_to_budget_fails_when_nothing_fits() {
+        let temp_dir = TempDir::new().unwrap();
+        create_test_cline_directory(&temp_dir).unwrap();
+        fs::write(temp_dir.path().join("Cargo.toml"), "[package]\nname = \"demo\"\n").unwrap();
+        
+        let mut options = GenerateOptions { context_depth: Some(1), ..GenerateOptions::default() };
+        options.tokens.budget = Some(1);
+        options.tokens.on_exceed = BudgetAction::Trim;
+        let generator = ClinerGenerator::new(ClinePaths::from_root(temp_dir.path()), options);
+        
+        let error = generator.generate_clinerules().unwrap_err();
+        assert!(matches!(error, ClinerError::BudgetExceeded { budget: 1, trimmed: true, .. }));
+        assert!(error.to_string().contains("into the budget of 1 tokens: it is still about"));
+    }
 }
@@ -1,91 +1,302 @@
-use crate::error::Result;
-use crate::models::ClinePaths;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use crate::error::{ClinerError, Result};
+use crate::models::{ClinePaths, CopyAction, CopyOutcome, InitLock, InitOptions, InitSource, InitSourceKind, SourceKind};
+use crate::processors::SourceCheckout;
+use crate::templates::{self, Template, TEMPLATES};
 
 pub struct ClinerInitializer {
     paths: ClinePaths,
+    options: InitOptions,
 }
 
 impl ClinerInitializer {
-    pub fn new() -> Self {
-        Self {
-            paths: ClinePaths::new(),
-        }
+    pub fn new(paths: ClinePaths, options: InitOptions) -> Self {
+        Self { paths, options }
     }
     
     pub fn run_init(&self) -> Result<()> {
-        println!("Initializing .cline directory...");
+        if self.options.dry_run {
+            println!("Dry run: no files will be changed");
+        } else {
+            println!("Initializing .cline directory...");
+            
+            self.paths.create_directories()?;
+            println!("Created .cline directory structure");
+        }
         
-        self.paths.create_directories()?;
-        println!("Created .cline directory structure");
+        let lock = match (&self.options.template, &self.options.from) {
+            (Some(template_name), _) => self.copy_from_template(template_name)?,
+            (None, Some(location)) => self.copy_from_source(location)?,
+            (None, None) if self.options.global_layer => {
+                println!("Global config directories are a source layer (layers.global); their files are not copied");
+                None
+            },
+            (None, None) => self.copy_from_global_configs()?,
+        };
         
-        self.copy_from_global_configs()?;
+        if let Some(lock) = lock && !self.options.dry_run {
+            self.record_init(lock)?;
+        }
         
         Ok(())
     }
     
-    fn copy_from_global_configs(&self) -> Result<()> {
-        let global_paths = ClinePaths::get_global_config_paths();
+    /// Saves the lock file and a snapshot of every copied file, the base `update` merges against.
+    /// Running `init` again from the same source keeps tracking the files it copied before.
+    fn record_init(&self, mut lock: InitLock) -> Result<()> {
+        for tracked_path in &lock.files {
+            let Some(project_file) = self.paths.tracked_file(tracked_path) else {
+                continue;
+            };
+            
+            let snapshot = self.paths.snapshot_file(tracked_path);
+            if let Some(parent) = snapshot.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(&project_file, &snapshot)?;
+        }
+        
+        // Files copied by an earlier run keep the snapshot they had.
+        let lock_path = self.paths.init_lock_file();
+        if let Some(previous) = InitLock::load(&lock_path)? {
+            let same_source = previous.source.kind == lock.source.kind && previous.source.location == lock.source.location;
+            if same_source {
+                for tracked_path in previous.files {
+                    let still_exists = self.paths.tracked_file(&tracked_path).is_some_and(|project_file| project_file.exists());
+                    if still_exists && !lock.files.contains(&tracked_path) {
+                        lock.files.push(tracked_path);
+                    }
+                }
+                lock.files.sort();
+            } else if !previous.files.is_empty() {
+                println!(
+                    "Replacing recorded source {}; the files copied from it are no longer updated",
+                    previous.source.location
+                );
+            }
+        }
+        
+        lock.save(&lock_path)?;
+        println!("Recorded source in {}", lock_path.display());
+        
+        Ok(())
+    }
+    
+    /// Paths relative to `.cline` of the files that now hold the source's content.
+    fn tracked_files(&self, outcomes: &[CopyOutcome], kind: SourceKind) -> Vec<PathBuf> {
+        let project_dir = self.paths.project_source_dir(kind);
+        outcomes
+            .iter()
+            .filter(|outcome| outcome.action != CopyAction::Skipped)
+            // Links from `init --link` follow their source, so `update` has nothing to merge.
+            .filter(|outcome| !outcome.destination.is_symlink())
+            .filter_map(|outcome| outcome.destination.strip_prefix(project_dir).ok())
+            .map(|relative_path| PathBuf::from(kind.dir_name()).join(relative_path))
+            .collect()
+    }
+    
+    pub fn render_template_list() -> String {
+        let mut listing = String::from("Available templates:\n");
+        for template in TEMPLATES {
+            listing.push_str(&format!("  {:<8} {}\n", template.name, template.description));
+        }
+        listing
+    }
+    
+    fn copy_from_template(&self, template_name: &str) -> Result<Option<InitLock>> {
+        let template = templates::find_template(template_name)
+            .ok_or_else(|| ClinerError::InvalidFormat(format!("Unknown template '{}'", template_name)))?;
+        
+        println!("Using template: {}", template.name);
+        let mut copied_files = Vec::new();
+        for kind in [SourceKind::Modes, SourceKind::Rules] {
+            let outcomes = self.write_template_files(template, kind)?;
+            self.report_outcomes(&outcomes, kind, &format!("template '{}'", template.name));
+            copied_files.extend(self.tracked_files(&outcomes, kind));
+        }
+        
+        let source = InitSource {
+            kind: InitSourceKind::Template,
+            location: template.name.to_string(),
+            reference: None,
+            commit: None,
+        };
+        Ok(Some(InitLock { source, files: copied_files }))
+    }
+    
+    fn copy_from_source(&self, location: &str) -> Result<Option<InitLock>> {
+        let checkout = SourceCheckout::fetch(location, self.options.reference.as_deref())?;
+        let content_root = checkout.content_root();
+        
+        match &checkout.source.commit {
+            Some(commit) => println!("Using source: {} at {}", location, commit),
+            None => println!("Using source: {}", location),
+        }
+        
+        let mut copied_files = Vec::new();
+        for kind in [SourceKind::Modes, SourceKind::Rules] {
+            let source_dir = content_root.join(kind.dir_name());
+            let project_dir = self.paths.project_source_dir(kind);
+            let mut outcomes = ClinePaths::copy_dir_contents(&source_dir, project_dir, kind, &self.options.filter, self.options.on_conflict, self.options.dry_run)?;
+            for outcome in &mut outcomes {
+                let relative_source = outcome.source.strip_prefix(&content_root).unwrap_or(&outcome.source);
+                outcome.source = PathBuf::from(location).join(relative_source);
+            }
+            
+            self.report_outcomes(&outcomes, kind, &format!("{}/{}", location, kind.dir_name()));
+            if outcomes.is_empty() {
+                continue;
+            }
+            copied_files.extend(self.tracked_files(&outcomes, kind));
+        }
+        
+        if copied_files.is_empty() {
+            println!("No mode or rule files found in {}", location);
+        }
+        
+        Ok(Some(InitLock { source: checkout.source.clone(), files: copied_files }))
+    }
+    
+    fn write_template_files(&self, template: &Template, kind: SourceKind) -> Result<Vec<CopyOutcome>> {
+        let project_dir = self.paths.project_source_dir(kind);
+        let mut outcomes = Vec::new();
+        
+        for file in template.files.iter().filter(|file| file.kind == kind) {
+            let destination = project_dir.join(file.file_name);
+            let action = ClinePaths::write_file_contents(
+                file.content.as_bytes(),
+                &destination,
+                self.options.on_conflict,
+                self.options.dry_run,
+            )?;
+            let source = PathBuf::from(format!("template:{}/{}/{}", template.name, kind.dir_name(), file.file_name));
+            outcomes.push(CopyOutcome { source, destination, action });
+        }
+        
+        Ok(outcomes)
+    }
+    
+    fn copy_from_global_configs(&self) -> Result<Option<InitLock>> {
+        let global_paths = &self.paths.global;
         if global_paths.is_empty() {
             println!("No global config directories found");
-            return Ok(());
+            return Ok(None);
         }
         
         println!("Found {} global config directories", global_paths.len());
         
-        let mut copied_modes = false;
-        let mut copied_rules = false;
+        let mut copied_modes = None;
+        let mut copied_rules = None;
         
         for global_path in global_paths {
             println!("Checking global config at: {}", global_path.display());
             
-            let global_modes_dir = global_path.join("modes");
-            if global_modes_dir.exists() && global_modes_dir.is_dir() && !copied_modes {
-                match ClinePaths::copy_dir_contents(&global_modes_dir, &self.paths.modes) {
-                    Ok(count) => {
-                        if count > 0 {
-                            println!("Copied {} mode files from {}", count, global_modes_dir.display());
-                            copied_modes = true;
-                        }
-                    },
-                    Err(e) => eprintln!("Error copying mode files: {}", e),
-                }
+            if copied_modes.is_none() {
+                copied_modes = self.copy_global_dir(global_path, SourceKind::Modes);
             }
             
-            let global_rules_dir = global_path.join("rules");
-            if global_rules_dir.exists() && global_rules_dir.is_dir() && !copied_rules {
-                match ClinePaths::copy_dir_contents(&global_rules_dir, &self.paths.rules) {
-                    Ok(count) => {
-                        if count > 0 {
-                            println!("Copied {} rule files from {}", count, global_rules_dir.display());
-                            copied_rules = true;
-                        }
-                    },
-                    Err(e) => eprintln!("Error copying rule files: {}", e),
-                }
+            if copied_rules.is_none() {
+                copied_rules = self.copy_global_dir(global_path, SourceKind::Rules);
             }
             
-            if copied_modes && copied_rules {
+            if copied_modes.is_some() && copied_rules.is_some() {
                 break;
             }
         }
         
-        if !copied_modes {
+        if copied_modes.is_none() {
             println!("No mode files found in global config directories");
         }
         
-        if !copied_rules {
+        if copied_rules.is_none() {
             println!("No rule files found in global config directories");
         }
         
-        Ok(())
+        if copied_modes.is_none() && copied_rules.is_none() {
+            return Ok(None);
+        }
+        
+        let source = InitSource {
+            kind: InitSourceKind::Global,
+            location: global_paths[0].display().to_string(),
+            reference: None,
+            commit: None,
+        };
+        let files = copied_modes.into_iter().chain(copied_rules).flatten().collect();
+        Ok(Some(InitLock { source, files }))
+    }
+    
+    /// Copies one kind of source from a global directory and prints a line per file.
+    /// Returns the copied files, or `None` if the directory had nothing to copy.
+    fn copy_global_dir(&self, global_path: &Path, kind: SourceKind) -> Option<Vec<PathBuf>> {
+        let global_dir = global_path.join(kind.dir_name());
+        if !global_dir.is_dir() {
+            return None;
+        }
+        
+        let project_dir = self.paths.project_source_dir(kind);
+        
+        let result = if self.options.link {
+            ClinePaths::link_dir_contents(&global_dir, project_dir, kind, &self.options.filter, self.options.on_conflict, self.options.dry_run)
+        } else {
+            ClinePaths::copy_dir_contents(&global_dir, project_dir, kind, &self.options.filter, self.options.on_conflict, self.options.dry_run)
+        };
+        
+        match result {
+            Ok(outcomes) => {
+                if outcomes.is_empty() {
+                    return None;
+                }
+                
+                self.report_outcomes(&outcomes, kind, &global_dir.display().to_string());
+                // Linked files follow the global copy, so there is nothing for `update` to merge.
+                Some(if self.options.link { Vec::new() } else { self.tracked_files(&outcomes, kind) })
+            },
+            Err(e) => {
+                eprintln!("Error copying {} files: {}", kind.item_name(), e);
+                None
+            },
+        }
+    }
+    
+    fn report_outcomes(&self, outcomes: &[CopyOutcome], kind: SourceKind, origin: &str) {
+        if outcomes.is_empty() {
+            return;
+        }
+        
+        println!("{}", Self::summarize(outcomes, kind, origin, self.options.dry_run, self.options.link));
+        for outcome in outcomes {
+            println!("  {}", outcome);
+        }
+    }
+    
+    fn summarize(outcomes: &[CopyOutcome], kind: SourceKind, origin: &str, dry_run: bool, link: bool) -> String {
+        let changed_count = outcomes.iter().filter(|outcome| outcome.action.changes_destination()).count();
+        let verb = match (dry_run, link) {
+            (true, true) => "Would link",
+            (true, false) => "Would copy",
+            (false, true) => "Linked",
+            (false, false) => "Copied",
+        };
+        
+        format!(
+            "{} {} of {} {} files from {}",
+            verb,
+            changed_count,
+            outcomes.len(),
+            kind.item_name(),
+            origin
+        )
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::fs;
     use tempfile::TempDir;
-    use std::path::Path;
 
     struct TestClinerInitializer {
         paths: ClinePaths,
@@ -93,12 +304,8 @@ mod tests {
     
     impl TestClinerInitializer {
         fn new(base_dir: &Path) -> Self {
-            let base = base_dir.join(".cline");
-            let modes = base.join("modes");
-            let rules = base.join("rules");
-            
             Self {
-                paths: ClinePaths { base, modes, rules }
+                paths: ClinePaths::from_root(base_dir)
             }
         }
         
@@ -129,4 +336,230 @@ mod tests {
         assert!(modes_dir.exists());
         assert!(rules_dir.exists());
     }
+    
+    #[test]
+    fn test_run_init_dry_run_creates_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+        let global_dir = temp_dir.path().join("global");
+        fs::create_dir_all(global_dir.join("rules")).unwrap();
+        fs::write(global_dir.join("rules").join("00_style.md"), "style").unwrap();
+        
+        let project_dir = temp_dir.path().join("project");
+        let mut paths = ClinePaths::from_root(&project_dir);
+        paths.global = vec![global_dir];
+        
+        let options = InitOptions { dry_run: true, ..InitOptions::default() };
+        let result = ClinerInitializer::new(paths, options).run_init();
+        
+        assert!(result.is_ok());
+        assert!(!project_dir.join(".cline").exists());
+    }
+    
+    #[test]
+    fn test_run_init_keeps_existing_files_by_default() {
+        let temp_dir = TempDir::new().unwrap();
+        let global_dir = temp_dir.path().join("global");
+        fs::create_dir_all(global_dir.join("rules")).unwrap();
+        fs::write(global_dir.join("rules").join("00_style.md"), "global style").unwrap();
+        fs::write(global_dir.join("rules").join("01_new.md"), "new rule").unwrap();
+        
+        let mut paths = ClinePaths::from_root(temp_dir.path());
+        fs::create_dir_all(&paths.rules).unwrap();
+        fs::write(paths.rules.join("00_style.md"), "edited style").unwrap();
+        paths.global = vec![global_dir];
+        let rules_dir = paths.rules.clone();
+        
+        let result = ClinerInitializer::new(paths, InitOptions::default()).run_init();
+        
+        assert!(result.is_ok());
+        assert_eq!(fs::read_to_string(rules_dir.join("00_style.md")).unwrap(), "edited style");
+        assert_eq!(fs::read_to_string(rules_dir.join("01_new.md")).unwrap(), "new rule");
+    }
+    
+    #[test]
+    fn test_summarize() {
+        let outcomes = vec![
+            CopyOutcome { source: "a".into(), destination: "b".into(), action: CopyAction::Copied },
+            CopyOutcome { source: "c".into(), destination: "d".into(), action: CopyAction::Skipped },
+        ];
+        
+        let summary = ClinerInitializer::summarize(&outcomes, SourceKind::Rules, "/global/rules", true, false);
+        
+        assert_eq!(summary, "Would copy 1 of 2 rule files from /global/rules");
+    }
+    
+    #[test]
+    fn test_run_init_from_template() {
+        let temp_dir = TempDir::new().unwrap();
+        let paths = ClinePaths::from_root(temp_dir.path());
+        let modes_dir = paths.modes.clone();
+        let rules_dir = paths.rules.clone();
+        
+        let options = InitOptions { template: Some("rust".to_string()), ..InitOptions::default() };
+        let result = ClinerInitializer::new(paths, options).run_init();
+        
+        assert!(result.is_ok());
+        assert!(modes_dir.join("00_rust-reviewer.md").exists());
+        assert!(fs::read_to_string(rules_dir.join("00_rust.md")).unwrap().contains("cargo fmt"));
+    }
+    
+    #[test]
+    fn test_run_init_unknown_template() {
+        let temp_dir = TempDir::new().unwrap();
+        let options = InitOptions { template: Some("cobol".to_string()), ..InitOptions::default() };
+        
+        let result = ClinerInitializer::new(ClinePaths::from_root(temp_dir.path()), options).run_init();
+        
+        assert!(result.is_err());
+    }
+    
+    #[test]
+    fn test_render_template_list() {
+        let listing = ClinerInitializer::render_template_list();
+        
+        assert!(listing.starts_with("Available templates:\n"));
+        assert!(listing.contains("  rust "));
+        assert!(listing.contains("  docs "));
+    }
+    
+    #[test]
+    fn test_run_init_with_global_layer_copies_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+        let global_dir = temp_dir.path().join("global");
+        fs::create_dir_all(global_dir.join("rules")).unwrap();
+        fs::write(global_dir.join("rules").join("00_style.md"), "global style").unwrap();
+        
+        let mut paths = ClinePaths::from_root(temp_dir.path().join("project"));
+        paths.global = vec![global_dir];
+        let copied_rule = paths.rules.join("00_style.md");
+        let lock_path = paths.init_lock_file();
+        
+        let options = InitOptions { global_layer: true, ..InitOptions::default() };
+        ClinerInitializer::new(paths, options).run_init().unwrap();
+        
+        assert!(!copied_rule.exists());
+        assert!(!lock_path.exists());
+    }
+    
+    #[test]
+    fn test_run_init_from_directory_records_source() {
+        let temp_dir = TempDir::new().unwrap();
+        let source_dir = temp_dir.path().join("org-rules");
+        fs::create_dir_all(source_dir.join("rules")).unwrap();
+        fs::write(source_dir.join("rules").join("00_org.md"), "org rule").unwrap();
+        
+        let paths = ClinePaths::from_root(temp_dir.path().join("project"));
+        let rules_dir = paths.rules.clone();
+        let lock_path = paths.init_lock_file();
+        let options = InitOptions { from: Some(source_dir.to_string_lossy().into_owned()), ..InitOptions::default() };
+        
+        let result = ClinerInitializer::new(paths, options).run_init();
+        
+        assert!(result.is_ok());
+        assert_eq!(fs::read_to_string(rules_dir.join("00_org.md")).unwrap(), "org rule");
+        
+        let lock = InitLock::load(&lock_path).unwrap().unwrap();
+        assert_eq!(lock.source.kind, InitSourceKind::Directory);
+        assert_eq!(lock.files, vec![PathBuf::from("rules/00_org.md")]);
+    }
+    
+    #[test]
+    fn test_run_init_again_keeps_tracking_earlier_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let source_dir = temp_dir.path().join("org-rules");
+        fs::create_dir_all(source_dir.join("rules")).unwrap();
+        fs::write(source_dir.join("rules").join("00_org.md"), "org rule").unwrap();
+        
+        let paths = ClinePaths::from_root(temp_dir.path().join("project"));
+        let lock_path = paths.init_lock_file();
+        let options = InitOptions { from: Some(source_dir.to_string_lossy().into_owned()), ..InitOptions::default() };
+        let initializer = ClinerInitializer::new(paths, options);
+        initializer.run_init().unwrap();
+        
+        fs::write(temp_dir.path().join("project/.cline/rules/00_org.md"), "edited org rule").unwrap();
+        fs::write(source_dir.join("rules").join("01_new.md"), "new rule").unwrap();
+        initializer.run_init().unwrap();
+        
+        let lock = InitLock::load(&lock_path).unwrap().unwrap();
+        assert_eq!(lock.files, vec![PathBuf::from("rules/00_org.md"), PathBuf::from("rules/01_new.md")]);
+        let snapshot = ClinePaths::from_root(temp_dir.path().join("project")).snapshot_file(Path::new("rules/00_org.md"));
+        assert_eq!(fs::read_to_string(snapshot).unwrap(), "org rule");
+        
+        let other_dir = temp_dir.path().join("other-rules");
+        fs::create_dir_all(other_dir.join("rules")).unwrap();
+        fs::write(other_dir.join("rules").join("02_other.md"), "other rule").unwrap();
+        let paths = ClinePaths::from_root(temp_dir.path().join("project"));
+        let options = InitOptions { from: Some(other_dir.to_string_lossy().into_owned()), ..InitOptions::default() };
+        ClinerInitializer::new(paths, options).run_init().unwrap();
+        
+        let lock = InitLock::load(&lock_path).unwrap().unwrap();
+        assert_eq!(lock.files, vec![PathBuf::from("rules/02_other.md")]);
+    }
+    
+    #[test]
+    fn test_run_init_snapshots_copied_files_only() {
+        let temp_dir = TempDir::new().unwrap();
+        let global_dir = temp_dir.path().join("global");
+        fs::create_dir_all(global_dir.join("rules")).unwrap();
+        fs::write(global_dir.join("rules").join("00_style.md"), "global style").unwrap();
+        fs::write(global_dir.join("rules").join("01_new.md"), "new rule").unwrap();
+        
+        let mut paths = ClinePaths::from_root(temp_dir.path());
+        fs::create_dir_all(&paths.rules).unwrap();
+        fs::write(paths.rules.join("00_style.md"), "edited style").unwrap();
+        paths.global = vec![global_dir];
+        let snapshot = paths.snapshot_file(Path::new("rules/01_new.md"));
+        let skipped_snapshot = paths.snapshot_file(Path::new("rules/00_style.md"));
+        let lock_path = paths.init_lock_file();
+        
+        let result = ClinerInitializer::new(paths, InitOptions::default()).run_init();
+        
+        assert!(result.is_ok());
+        assert_eq!(fs::read_to_string(snapshot).unwrap(), "new rule");
+        assert!(!skipped_snapshot.exists());
+        
+        let lock = InitLock::load(&lock_path).unwrap().unwrap();
+        assert_eq!(lock.source.kind, InitSourceKind::Global);
+        assert_eq!(lock.files, vec![PathBuf::from("rules/01_new.md")]);
+    }
+    
+    #[cfg(unix)]
+    #[test]
+    fn test_run_init_link() {
+        let temp_dir = TempDir::new().unwrap();
+        let global_dir = temp_dir.path().join("global");
+        fs::create_dir_all(global_dir.join("rules")).unwrap();
+        fs::write(global_dir.join("rules").join("00_style.md"), "global style").unwrap();
+        
+        let mut paths = ClinePaths::from_root(temp_dir.path().join("project"));
+        paths.global = vec![global_dir.clone()];
+        let linked_rule = paths.rules.join("00_style.md");
+        
+        let options = InitOptions { link: true, ..InitOptions::default() };
+        let result = ClinerInitializer::new(paths, options).run_init();
+        
+        assert!(result.is_ok());
+        assert!(linked_rule.is_symlink());
+        fs::write(global_dir.join("rules").join("00_style.md"), "edited style").unwrap();
+        assert_eq!(fs::read_to_string(&linked_rule).unwrap(), "edited style");
+    }
+    
+    #[cfg(unix)]
+    #[test]
+    fn test_run_init_after_link_does_not_track_links() {
+        let temp_dir = TempDir::new().unwrap();
+        let global_dir = temp_dir.path().join("global");
+        fs::create_dir_all(global_dir.join("rules")).unwrap();
+        fs::write(global_dir.join("rules").join("00_style.md"), "global style").unwrap();
+        
+        let mut paths = ClinePaths::from_root(temp_dir.path().join("project"));
+        paths.global = vec![global_dir.clone()];
+        let lock_path = paths.init_lock_file();
+        let snapshot = paths.snapshot_file(Path::new("rules/00_style.md"));
+        ClinerInitializer::new(paths.clone(), InitOptions { link: true, ..InitOptions::default() }).run_init().unwrap();
+        ClinerInitializer::new(paths, InitOptions::default()).run_init().unwrap();
+        
+        assert!(InitLock::load(&lock_path).unwrap().unwrap().files.is_empty());
+        assert!(!snapshot.exists());
+    }
 }
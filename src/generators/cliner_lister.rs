@@ -0,0 +1,281 @@
+use std::fs;
+use std::path::Path;
+use crate::error::Result;
+use crate::models::{ClinePaths, GenerateOptions, GlobalConfigCandidate, InitLock, SourceFile, SourceKind};
+use crate::processors::FileProcessor;
+
+pub struct ClinerLister {
+    paths: ClinePaths,
+    options: GenerateOptions,
+}
+
+impl ClinerLister {
+    pub fn new(paths: ClinePaths, options: GenerateOptions) -> Self {
+        Self { paths, options }
+    }
+    
+    pub fn run_list(&self) -> Result<()> {
+        print!("{}", self.render_listing()?);
+        Ok(())
+    }
+    
+    pub fn render_listing(&self) -> Result<String> {
+        let mut listing = String::new();
+        
+        for (kind, heading) in [(SourceKind::Modes, "Modes:"), (SourceKind::Rules, "Rules:")] {
+            let source_dirs = self.paths.source_dirs(kind);
+            let collected = FileProcessor::select_sources(&source_dirs, kind, &self.options)?;
+            let sources = collected.files;
+            
+            listing.push_str(heading);
+            listing.push('\n');
+            
+            if sources.is_empty() {
+                listing.push_str("  (none)\n");
+            }
+            for source in &sources {
+                listing.push_str(&self.render_source(source));
+                listing.push('\n');
+            }
+            
+            if self.options.verbose {
+                for skipped in &collected.skipped {
+                    listing.push_str(&format!("  {:<14} {} ({})\n", "[skipped]", self.display_path(&skipped.path), skipped.reason.description()));
+                }
+            }
+        }
+        
+        Ok(listing)
+    }
+    
+    pub fn run_config_paths(&self, candidates: &[GlobalConfigCandidate], global_enabled: bool) -> Result<()> {
+        print!("{}", self.render_config_paths(candidates, global_enabled)?);
+        Ok(())
+    }
+    
+    pub fn render_config_paths(&self, candidates: &[GlobalConfigCandidate], global_enabled: bool) -> Result<String> {
+        let mut report = String::from("Global config search order (highest precedence first):\n");
+        if !global_enabled {
+            report.push_str("  (global layer disabled)\n");
+        }
+        for (index, candidate) in candidates.iter().enumerate() {
+            report.push_str(&format!(
+                "  {}. {} ({}) {}\n",
+                index + 1,
+                candidate.path.display(),
+                candidate.origin,
+                Self::existence_label(&candidate.path),
+            ));
+        }
+        
+        let config_file = self.paths.config_file();
+        report.push_str("\nProject:\n");
+        report.push_str(&format!("  root: {}\n", self.paths.root.display()));
+        report.push_str(&format!("  config: {} {}\n", config_file.display(), Self::existence_label(&config_file)));
+        if let Some(lock) = InitLock::load(&self.paths.init_lock_file())? {
+            report.push_str(&format!("  initialized from: {}", lock.source.location));
+            if let Some(reference) = &lock.source.reference {
+                report.push_str(&format!(" ({})", reference));
+            }
+            if let Some(commit) = &lock.source.commit {
+                report.push_str(&format!(" at {}", commit));
+            }
+            report.push('\n');
+        }
+        
+        for (kind, heading) in [(SourceKind::Modes, "Mode"), (SourceKind::Rules, "Rule")] {
+            report.push_str(&format!("\n{} directories (lowest precedence first):\n", heading));
+            for source_dir in self.paths.source_dirs(kind) {
+                let layer = format!("[{}]", source_dir.layer);
+                report.push_str(&format!(
+                    "  {:<14} {} {}\n",
+                    layer,
+                    source_dir.path.display(),
+                    Self::existence_label(&source_dir.path),
+                ));
+            }
+        }
+        
+        Ok(report)
+    }
+    
+    fn existence_label(path: &Path) -> &'static str {
+        if path.exists() { "[found]" } else { "[missing]" }
+    }
+    
+    fn render_source(&self, source: &SourceFile) -> String {
+        let layer = format!("[{}]", source.layer);
+        let mut line = format!("  {:<14} {}", layer, self.display_path(&source.path));
+        
+        if !source.overrides.is_empty() {
+            let overridden: Vec<&str> = source.overrides.iter().map(|layer| layer.as_str()).collect();
+            line.push_str(&format!(" (overrides {})", overridden.join(", ")));
+        }
+        
+        let tags = source.tags();
+        if !tags.is_empty() {
+            line.push_str(&format!(" (tags: {})", tags.join(", ")));
+        }
+        
+        if let Ok(target) = fs::read_link(&source.path) {
+            line.push_str(&format!(" (linked to {})", target.display()));
+        }
+        
+        if let Some(title) = &source.metadata.title {
+            line.push_str(&format!(" - {}", title));
+        }
+        if let Some(description) = &source.metadata.description {
+            line.push_str(&format!("\n  {:<14} {}", "", description));
+        }
+        
+        line
+    }
+    
+    fn display_path(&self, path: &Path) -> String {
+        let relative_path = path.strip_prefix(&self.paths.root).unwrap_or(path);
+        relative_path.display().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+    use crate::models::{InitSource, InitSourceKind, SourceFilter};
+    
+    #[test]
+    fn test_render_listing_shows_layers() {
+        let project_dir = TempDir::new().unwrap();
+        let global_dir = TempDir::new().unwrap();
+        
+        fs::create_dir_all(global_dir.path().join("rules")).unwrap();
+        fs::write(global_dir.path().join("rules").join("00_style.md"), "global").unwrap();
+        fs::write(global_dir.path().join("rules").join("01_commits.md"), "global").unwrap();
+        
+        let rules_dir = project_dir.path().join(".cline").join("rules");
+        fs::create_dir_all(&rules_dir).unwrap();
+        fs::write(rules_dir.join("00_style.md"), "project").unwrap();
+        
+        let mut paths = ClinePaths::from_root(project_dir.path());
+        paths.global = vec![global_dir.path().to_path_buf()];
+        
+        let lister = ClinerLister::new(paths, GenerateOptions::default());
+        let listing = lister.render_listing().unwrap();
+        
+        assert!(listing.contains("Modes:\n  (none)\n"));
+        assert!(listing.contains("[project]      .cline/rules/00_style.md (overrides global)"));
+        assert!(listing.contains("[global]"));
+        assert!(listing.contains("01_commits.md"));
+    }
+    
+    #[test]
+    fn test_render_listing_nested_rules() {
+        let project_dir = TempDir::new().unwrap();
+        let rules_dir = project_dir.path().join(".cline").join("rules");
+        fs::create_dir_all(rules_dir.join("backend")).unwrap();
+        fs::write(rules_dir.join("backend").join("00_api.md"), "api").unwrap();
+        fs::write(rules_dir.join("00_style.md"), "style").unwrap();
+        
+        let lister = ClinerLister::new(ClinePaths::from_root(project_dir.path()), GenerateOptions::default());
+        let listing = lister.render_listing().unwrap();
+        
+        assert!(listing.contains(".cline/rules/00_style.md\n  [project]      .cline/rules/backend/00_api.md (tags: backend)\n"));
+    }
+    
+    #[test]
+    fn test_render_listing_shows_title_and_description() {
+        let project_dir = TempDir::new().unwrap();
+        let paths = ClinePaths::from_root(project_dir.path());
+        fs::create_dir_all(&paths.rules).unwrap();
+        fs::write(paths.rules.join("00_security.md"), "---\ntitle: Security\ndescription: Secrets and user input\n---\nNever log tokens.").unwrap();
+        
+        let listing = ClinerLister::new(paths, GenerateOptions::default()).render_listing().unwrap();
+        
+        assert!(listing.contains("[project]      .cline/rules/00_security.md - Security\n                 Secrets and user input\n"), "{}", listing);
+    }
+    
+    #[test]
+    fn test_render_listing_verbose_shows_skipped_files() {
+        let project_dir = TempDir::new().unwrap();
+        let paths = ClinePaths::from_root(project_dir.path());
+        fs::create_dir_all(paths.rules.join("drafts")).unwrap();
+        fs::write(paths.rules.join("00_style.md"), "style").unwrap();
+        fs::write(paths.rules.join("00_style.md.bak"), "old style").unwrap();
+        fs::write(paths.rules.join("drafts").join("00.md"), "draft").unwrap();
+        fs::write(paths.ignore_file(), "rules/drafts/\n").unwrap();
+        
+        let filter = SourceFilter::new(&["*.md".to_string()], &[], Some(&paths.ignore_file())).unwrap();
+        let mut options = GenerateOptions::default().with_filter(filter);
+        options.verbose = true;
+        let listing = ClinerLister::new(paths, options).render_listing().unwrap();
+        
+        assert!(listing.contains("[skipped]      .cline/rules/00_style.md.bak (not matched by sources.include)\n"));
+        assert!(listing.contains("[skipped]      .cline/rules/drafts/00.md (ignored by .clineignore)\n"));
+        assert!(!listing.contains("[project]      .cline/rules/drafts"));
+    }
+    
+    #[cfg(unix)]
+    #[test]
+    fn test_render_listing_marks_linked_files() {
+        let project_dir = TempDir::new().unwrap();
+        let global_rule = project_dir.path().join("global.md");
+        fs::write(&global_rule, "global").unwrap();
+        
+        let rules_dir = project_dir.path().join(".cline").join("rules");
+        fs::create_dir_all(&rules_dir).unwrap();
+        std::os::unix::fs::symlink(&global_rule, rules_dir.join("00_style.md")).unwrap();
+        
+        let lister = ClinerLister::new(ClinePaths::from_root(project_dir.path()), GenerateOptions::default());
+        let listing = lister.render_listing().unwrap();
+        
+        assert!(listing.contains(&format!(".cline/rules/00_style.md (linked to {})", global_rule.display())));
+    }
+    
+    #[test]
+    fn test_render_config_paths() {
+        let project_dir = TempDir::new().unwrap();
+        let team_dir = TempDir::new().unwrap();
+        fs::create_dir_all(project_dir.path().join(".cline").join("rules")).unwrap();
+        
+        let mut paths = ClinePaths::from_root(project_dir.path());
+        paths.global = vec![team_dir.path().to_path_buf()];
+        let candidates = vec![
+            GlobalConfigCandidate { path: team_dir.path().to_path_buf(), origin: "$CLINER_HOME" },
+            GlobalConfigCandidate { path: project_dir.path().join("nowhere"), origin: "$HOME/.cline" },
+        ];
+        
+        let lister = ClinerLister::new(paths, GenerateOptions::default());
+        let report = lister.render_config_paths(&candidates, true).unwrap();
+        
+        assert!(report.contains(&format!("  1. {} ($CLINER_HOME) [found]", team_dir.path().display())));
+        assert!(report.contains("($HOME/.cline) [missing]"));
+        assert!(report.contains("cliner.toml [missing]"));
+        assert!(report.contains(&format!("[project]      {} [found]", project_dir.path().join(".cline").join("rules").display())));
+        assert!(report.contains("[global]"));
+        assert!(!report.contains("disabled"));
+        assert!(!report.contains("initialized from"));
+    }
+    
+    #[test]
+    fn test_render_config_paths_shows_init_source() {
+        let project_dir = TempDir::new().unwrap();
+        let paths = ClinePaths::from_root(project_dir.path());
+        fs::create_dir_all(&paths.base).unwrap();
+        let lock = InitLock {
+            source: InitSource {
+                kind: InitSourceKind::Git,
+                location: "file:///srv/rules.git".to_string(),
+                reference: Some("v1".to_string()),
+                commit: Some("0123abcd".to_string()),
+            },
+            files: Vec::new(),
+        };
+        lock.save(&paths.init_lock_file()).unwrap();
+        
+        let lister = ClinerLister::new(paths, GenerateOptions::default());
+        let report = lister.render_config_paths(&[], true).unwrap();
+        
+        assert!(report.contains("  initialized from: file:///srv/rules.git (v1) at 0123abcd\n"));
+    }
+}
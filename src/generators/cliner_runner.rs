@@ -1,6 +1,7 @@
 use crate::error::Result;
-use crate::models::{Cli, Commands};
-use crate::generators::{ClinerGenerator, ClinerInitializer};
+use crate::models::{Cli, ClinePaths, ClinerConfig, Commands, ConfigCommands, GenerateArgs, GenerateOptions, GlobalConfigEnv, InitOptions, SelectionArgs, SourceFilter, UpdateOptions};
+use crate::generators::{ClinerGenerator, ClinerInitializer, ClinerLister, ClinerUpdater};
+use crate::processors::ProjectContext;
 use clap::{CommandFactory, Parser};
 
 pub struct ClinerRunner;
@@ -14,21 +15,127 @@ impl ClinerRunner {
     pub fn run() -> Result<()> {
         let cli = Cli::parse();
         
-        match cli.command {
-            Some(Commands::Init) => {
-                let initializer = ClinerInitializer::new();
+        match &cli.command {
+            Some(Commands::Init(args)) if args.list_templates => {
+                print!("{}", ClinerInitializer::render_template_list());
+                Ok(())
+            },
+            Some(Commands::Init(args)) => {
+                let mut paths = cli.root.as_deref().map(ClinePaths::from_root).unwrap_or_default();
+                let config = ClinerConfig::load(&paths.config_file())?;
+                if !cli.no_global && args.template.is_none() && args.from.is_none() {
+                    paths.use_global_config_paths(&cli.global_dirs);
+                }
+                let options = InitOptions {
+                    on_conflict: args.on_conflict,
+                    dry_run: args.dry_run,
+                    link: args.link,
+                    template: args.template.clone(),
+                    from: args.from.clone(),
+                    reference: args.reference.clone(),
+                    global_layer: config.layers.global && !cli.no_global,
+                    filter: Self::source_filter(&config, &paths)?,
+                };
+                let initializer = ClinerInitializer::new(paths, options);
                 initializer.run_init()
             },
-            Some(Commands::Generate) => {
-                let generator = ClinerGenerator::new();
+            Some(Commands::Generate(args)) => {
+                let generator = Self::generator(&cli, args, false)?;
                 generator.run_generate()
             },
+            Some(Commands::Check(args)) => {
+                let generator = Self::generator(&cli, args, true)?;
+                generator.run_check()
+            },
+            Some(Commands::Stats(args)) => {
+                let generator = Self::generator(&cli, args, true)?;
+                generator.run_stats()
+            },
+            Some(Commands::Update(args)) => {
+                let mut paths = ClinePaths::resolve(cli.root.as_deref())?;
+                let config = ClinerConfig::load(&paths.config_file())?;
+                paths.apply_config(&config);
+                if !cli.no_global {
+                    paths.use_global_config_paths(&cli.global_dirs);
+                }
+                
+                let options = UpdateOptions {
+                    dry_run: args.dry_run,
+                    reference: args.reference.clone(),
+                    filter: Self::source_filter(&config, &paths)?,
+                };
+                let updater = ClinerUpdater::new(paths, options);
+                updater.run_update()
+            },
+            Some(Commands::List(args)) => {
+                let (paths, config) = Self::load_project(&cli)?;
+                let options = Self::generate_options(&cli, &config, &paths, &GenerateArgs::default(), &args.selection)?;
+                let lister = ClinerLister::new(paths, options);
+                lister.run_list()
+            },
+            Some(Commands::Context(args)) => {
+                let (paths, config) = Self::load_project(&cli)?;
+                let depth = args.depth.unwrap_or(config.context.depth);
+                print!("{}", ProjectContext::render(&paths.root, &paths.context_dir(), depth)?);
+                Ok(())
+            },
+            Some(Commands::Config { command: ConfigCommands::Paths }) => {
+                let (paths, config) = Self::load_project(&cli)?;
+                let candidates = ClinePaths::global_config_candidates(&cli.global_dirs, &GlobalConfigEnv::from_env());
+                let global_enabled = config.layers.global && !cli.no_global;
+                let lister = ClinerLister::new(paths, GenerateOptions::from_config(&config));
+                lister.run_config_paths(&candidates, global_enabled)
+            },
             None => {
                 Self::show_help();
                 Ok(())
             }
         }
     }
+    
+    fn generator(cli: &Cli, args: &GenerateArgs, cached_commands: bool) -> Result<ClinerGenerator> {
+        let (mut paths, config) = Self::load_project(cli)?;
+        for (target, path) in &args.out {
+            paths.set_output_path(*target, path.clone());
+        }
+        
+        let mut options = Self::generate_options(cli, &config, &paths, args, &args.selection)?;
+        options.cached_commands = cached_commands;
+        Ok(ClinerGenerator::new(paths, options))
+    }
+    
+    fn generate_options(
+        cli: &Cli,
+        config: &ClinerConfig,
+        paths: &ClinePaths,
+        args: &GenerateArgs,
+        selection: &SelectionArgs,
+    ) -> Result<GenerateOptions> {
+        let filter = Self::source_filter(config, paths)?;
+        let mut options = GenerateOptions::from_config(config)
+            .with_args(args)
+            .with_filter(filter)
+            .with_selection(config, selection)?;
+        options.verbose = cli.verbose;
+        Ok(options)
+    }
+    
+    /// The files that count as sources: `sources.include`/`exclude` and `.clineignore`.
+    fn source_filter(config: &ClinerConfig, paths: &ClinePaths) -> Result<SourceFilter> {
+        SourceFilter::new(&config.sources.include, &config.sources.exclude, Some(&paths.ignore_file()))
+    }
+    
+    fn load_project(cli: &Cli) -> Result<(ClinePaths, ClinerConfig)> {
+        let mut paths = ClinePaths::resolve(cli.root.as_deref())?;
+        let config = ClinerConfig::load(&paths.config_file())?;
+        paths.apply_config(&config);
+        
+        if config.layers.global && !cli.no_global {
+            paths.use_global_config_paths(&cli.global_dirs);
+        }
+        
+        Ok((paths, config))
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,378 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::error::{ClinerError, Result};
+use crate::models::{ClinePaths, InitLock, InitSource, InitSourceKind, SortOrder, SourceKind, UpdateAction, UpdateOptions, UpdateOutcome};
+use crate::processors::{FileProcessor, SourceCheckout, ThreeWayMerge};
+use crate::templates;
+
+/// Upstream file contents keyed by path relative to `.cline`, e.g. `rules/00_style.md`.
+type UpstreamFiles = BTreeMap<PathBuf, Vec<u8>>;
+
+pub struct ClinerUpdater {
+    paths: ClinePaths,
+    options: UpdateOptions,
+}
+
+impl ClinerUpdater {
+    pub fn new(paths: ClinePaths, options: UpdateOptions) -> Self {
+        Self { paths, options }
+    }
+    
+    pub fn run_update(&self) -> Result<()> {
+        let lock_path = self.paths.init_lock_file();
+        let lock = InitLock::load(&lock_path)?.ok_or_else(|| {
+            ClinerError::InvalidFormat(format!(
+                "{} not found; run `cliner init` before `cliner update`",
+                lock_path.display()
+            ))
+        })?;
+        
+        if self.options.dry_run {
+            println!("Dry run: no files will be changed");
+        }
+        
+        let (source, upstream) = self.fetch_upstream(&lock.source)?;
+        match &source.commit {
+            Some(commit) => println!("Updating from {} at {}", source.location, commit),
+            None => println!("Updating from {}", source.location),
+        }
+        
+        let tracked: BTreeSet<&PathBuf> = lock.files.iter().collect();
+        // Tracked files keep being updated; new upstream files only if `generate` would use them.
+        let new_files = upstream.keys().filter(|path| !tracked.contains(path) && self.is_copied(path));
+        let candidates: BTreeSet<&PathBuf> = tracked.iter().copied().chain(new_files).collect();
+        
+        let mut outcomes = Vec::new();
+        let mut files = Vec::new();
+        for tracked_path in candidates {
+            let Some(destination) = self.paths.tracked_file(tracked_path) else {
+                continue;
+            };
+            // Files created by `init --link` already follow their source.
+            if destination.is_symlink() {
+                continue;
+            }
+            
+            let base = if tracked.contains(tracked_path) {
+                Self::read_optional(&self.paths.snapshot_file(tracked_path))?
+            } else {
+                None
+            };
+            let local = Self::read_optional(&destination)?;
+            let theirs = upstream.get(tracked_path);
+            
+            let (action, content) = Self::resolve(base.as_deref(), local.as_deref(), theirs.map(Vec::as_slice));
+            if !self.options.dry_run {
+                self.apply(tracked_path, &destination, action, content, theirs)?;
+            }
+            
+            if theirs.is_some() && Self::stays_tracked(action) {
+                files.push(tracked_path.clone());
+            }
+            outcomes.push(UpdateOutcome { destination, action });
+        }
+        
+        println!("{}", Self::summarize(&outcomes, self.options.dry_run));
+        for outcome in &outcomes {
+            println!("  {}", outcome);
+        }
+        
+        if !self.options.dry_run {
+            InitLock { source, files }.save(&lock_path)?;
+            println!("Recorded source in {}", lock_path.display());
+        }
+        
+        let conflicted = outcomes.iter().filter(|outcome| matches!(outcome.action, UpdateAction::Conflict(_))).count();
+        if conflicted > 0 {
+            let verb = if self.options.dry_run { "would have" } else { "have" };
+            return Err(ClinerError::MergeConflict(format!(
+                "{} file(s) {} conflict markers; resolve them before running `cliner generate`",
+                conflicted, verb
+            )));
+        }
+        
+        Ok(())
+    }
+    
+    /// Decides what to write for one file from the last snapshot (`base`), the project's copy and upstream.
+    fn resolve(base: Option<&[u8]>, local: Option<&[u8]>, upstream: Option<&[u8]>) -> (UpdateAction, Option<Vec<u8>>) {
+        match (base, local, upstream) {
+            (Some(_), None, _) => (UpdateAction::DeletedLocally, None),
+            (None, Some(local), Some(upstream)) if local != upstream => (UpdateAction::Untracked, None),
+            (None, None, Some(upstream)) => (UpdateAction::Added, Some(upstream.to_vec())),
+            _ if local == upstream => (UpdateAction::UpToDate, None),
+            _ if base == upstream => (UpdateAction::KeptLocal, None),
+            (_, _, None) if local == base => (UpdateAction::Removed, None),
+            (_, _, None) => (UpdateAction::Orphaned, None),
+            (_, _, Some(upstream)) if local == base => (UpdateAction::Updated, Some(upstream.to_vec())),
+            (base, local, Some(upstream)) => {
+                let result = ThreeWayMerge::merge(
+                    &String::from_utf8_lossy(base.unwrap_or_default()),
+                    &String::from_utf8_lossy(local.unwrap_or_default()),
+                    &String::from_utf8_lossy(upstream),
+                );
+                let action = if result.conflicts > 0 { UpdateAction::Conflict(result.conflicts) } else { UpdateAction::Merged };
+                (action, Some(result.content.into_bytes()))
+            },
+        }
+    }
+    
+    fn stays_tracked(action: UpdateAction) -> bool {
+        !matches!(action, UpdateAction::DeletedLocally | UpdateAction::Untracked | UpdateAction::Orphaned | UpdateAction::Removed)
+    }
+    
+    fn apply(
+        &self,
+        tracked_path: &Path,
+        destination: &Path,
+        action: UpdateAction,
+        content: Option<Vec<u8>>,
+        upstream: Option<&Vec<u8>>,
+    ) -> Result<()> {
+        if let Some(content) = content {
+            if let Some(parent) = destination.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(destination, content)?;
+        }
+        
+        if action == UpdateAction::Removed {
+            fs::remove_file(destination)?;
+        }
+        
+        let snapshot = self.paths.snapshot_file(tracked_path);
+        match upstream {
+            Some(upstream) if Self::stays_tracked(action) => {
+                if let Some(parent) = snapshot.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&snapshot, upstream)?;
+            },
+            _ if snapshot.exists() => fs::remove_file(&snapshot)?,
+            _ => {},
+        }
+        
+        Ok(())
+    }
+    
+    fn fetch_upstream(&self, source: &InitSource) -> Result<(InitSource, UpstreamFiles)> {
+        if self.options.reference.is_some() && !matches!(source.kind, InitSourceKind::Directory | InitSourceKind::Git) {
+            return Err(ClinerError::InvalidFormat(
+                "--ref only applies to projects initialized with --from".to_string(),
+            ));
+        }
+        
+        match source.kind {
+            InitSourceKind::Template => {
+                let template = templates::find_template(&source.location).ok_or_else(|| {
+                    ClinerError::InvalidFormat(format!("Unknown template '{}'", source.location))
+                })?;
+                let files = template
+                    .files
+                    .iter()
+                    .map(|file| (PathBuf::from(file.kind.dir_name()).join(file.file_name), file.content.as_bytes().to_vec()))
+                    .collect();
+                Ok((source.clone(), files))
+            },
+            InitSourceKind::Global => self.fetch_global(),
+            InitSourceKind::Directory | InitSourceKind::Git => {
+                let reference = self.options.reference.as_deref().or(source.reference.as_deref());
+                let checkout = SourceCheckout::fetch(&source.location, reference)?;
+                let content_root = checkout.content_root();
+                
+                let mut files = UpstreamFiles::new();
+                for kind in [SourceKind::Modes, SourceKind::Rules] {
+                    files.extend(Self::read_source_dir(&content_root.join(kind.dir_name()), kind)?);
+                }
+                Ok((checkout.source.clone(), files))
+            },
+        }
+    }
+    
+    /// Reads each kind from the first global directory that has files for it, as `init` does.
+    fn fetch_global(&self) -> Result<(InitSource, UpstreamFiles)> {
+        let first_global = self.paths.global.first().ok_or_else(|| {
+            ClinerError::InvalidFormat("No global config directories found".to_string())
+        })?;
+        
+        let mut files = UpstreamFiles::new();
+        for kind in [SourceKind::Modes, SourceKind::Rules] {
+            for global_path in &self.paths.global {
+                let kind_files = Self::read_source_dir(&global_path.join(kind.dir_name()), kind)?;
+                if !kind_files.is_empty() {
+                    files.extend(kind_files);
+                    break;
+                }
+            }
+        }
+        
+        let source = InitSource {
+            kind: InitSourceKind::Global,
+            location: first_global.display().to_string(),
+            reference: None,
+            commit: None,
+        };
+        Ok((source, files))
+    }
+    
+    fn read_source_dir(dir: &Path, kind: SourceKind) -> Result<UpstreamFiles> {
+        let mut files = UpstreamFiles::new();
+        if !dir.is_dir() {
+            return Ok(files);
+        }
+        
+        for relative_path in FileProcessor::collect_sorted_files(dir, SortOrder::default())? {
+            let content = fs::read(dir.join(&relative_path))?;
+            files.insert(PathBuf::from(kind.dir_name()).join(relative_path), content);
+        }
+        
+        Ok(files)
+    }
+    
+    fn is_copied(&self, tracked_path: &Path) -> bool {
+        let mut components = tracked_path.components();
+        let kind = components.next().and_then(|dir| SourceKind::from_dir_name(dir.as_os_str().to_str()?));
+        kind.is_some_and(|kind| self.options.filter.is_copied(kind, components.as_path()))
+    }
+    
+    fn read_optional(path: &Path) -> Result<Option<Vec<u8>>> {
+        if path.is_file() { Ok(Some(fs::read(path)?)) } else { Ok(None) }
+    }
+    
+    fn summarize(outcomes: &[UpdateOutcome], dry_run: bool) -> String {
+        let changed_count = outcomes.iter().filter(|outcome| outcome.action.changes_destination()).count();
+        let verb = if dry_run { "Would change" } else { "Changed" };
+        
+        format!("{} {} of {} tracked files", verb, changed_count, outcomes.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use crate::generators::ClinerInitializer;
+    use crate::models::InitOptions;
+    
+    fn init_from(source_dir: &Path, project_dir: &Path) -> ClinePaths {
+        let paths = ClinePaths::from_root(project_dir);
+        let options = InitOptions { from: Some(source_dir.to_string_lossy().into_owned()), ..InitOptions::default() };
+        ClinerInitializer::new(paths.clone(), options).run_init().unwrap();
+        paths
+    }
+    
+    #[test]
+    fn test_resolve() {
+        let resolve = |base: Option<&str>, local: Option<&str>, upstream: Option<&str>| {
+            ClinerUpdater::resolve(base.map(str::as_bytes), local.map(str::as_bytes), upstream.map(str::as_bytes)).0
+        };
+        
+        assert_eq!(resolve(Some("a"), Some("a"), Some("a")), UpdateAction::UpToDate);
+        assert_eq!(resolve(Some("a"), Some("b"), Some("a")), UpdateAction::KeptLocal);
+        assert_eq!(resolve(Some("a"), Some("a"), Some("b")), UpdateAction::Updated);
+        assert_eq!(resolve(None, None, Some("b")), UpdateAction::Added);
+        assert_eq!(resolve(None, Some("x"), Some("b")), UpdateAction::Untracked);
+        assert_eq!(resolve(Some("a"), Some("a"), None), UpdateAction::Removed);
+        assert_eq!(resolve(Some("a"), Some("b"), None), UpdateAction::Orphaned);
+        assert_eq!(resolve(Some("a"), None, Some("b")), UpdateAction::DeletedLocally);
+        assert_eq!(resolve(Some("a\n"), Some("b\n"), Some("c\n")), UpdateAction::Conflict(1));
+    }
+    
+    #[test]
+    fn test_run_update_merges_local_and_upstream_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        let source_dir = temp_dir.path().join("org");
+        let source_rules = source_dir.join("rules");
+        fs::create_dir_all(&source_rules).unwrap();
+        fs::write(source_rules.join("00_style.md"), "# Style\nUse tabs\nKeep lines short\n").unwrap();
+        fs::write(source_rules.join("01_old.md"), "old rule\n").unwrap();
+        
+        let paths = init_from(&source_dir, &temp_dir.path().join("project"));
+        fs::write(paths.rules.join("00_style.md"), "# Style\nUse tabs\nKeep lines short\nProject note\n").unwrap();
+        
+        fs::write(source_rules.join("00_style.md"), "# Style\nUse spaces\nKeep lines short\n").unwrap();
+        fs::remove_file(source_rules.join("01_old.md")).unwrap();
+        fs::write(source_rules.join("02_new.md"), "new rule\n").unwrap();
+        
+        let result = ClinerUpdater::new(paths.clone(), UpdateOptions::default()).run_update();
+        
+        assert!(result.is_ok());
+        assert_eq!(
+            fs::read_to_string(paths.rules.join("00_style.md")).unwrap(),
+            "# Style\nUse spaces\nKeep lines short\nProject note\n"
+        );
+        assert!(!paths.rules.join("01_old.md").exists());
+        assert_eq!(fs::read_to_string(paths.rules.join("02_new.md")).unwrap(), "new rule\n");
+        
+        let lock = InitLock::load(&paths.init_lock_file()).unwrap().unwrap();
+        assert_eq!(lock.files, vec![PathBuf::from("rules/00_style.md"), PathBuf::from("rules/02_new.md")]);
+        assert_eq!(
+            fs::read_to_string(paths.snapshot_file(Path::new("rules/00_style.md"))).unwrap(),
+            "# Style\nUse spaces\nKeep lines short\n"
+        );
+    }
+    
+    #[test]
+    fn test_run_update_only_adds_files_generate_would_use() {
+        let temp_dir = TempDir::new().unwrap();
+        let source_dir = temp_dir.path().join("org");
+        fs::create_dir_all(source_dir.join("rules")).unwrap();
+        fs::write(source_dir.join("rules").join("00_style.md"), "v1\n").unwrap();
+        
+        let paths = init_from(&source_dir, &temp_dir.path().join("project"));
+        fs::write(source_dir.join("rules").join("01_new.md"), "new rule\n").unwrap();
+        fs::write(source_dir.join("rules").join(".DS_Store"), "junk").unwrap();
+        
+        ClinerUpdater::new(paths.clone(), UpdateOptions::default()).run_update().unwrap();
+        
+        assert!(paths.rules.join("01_new.md").exists());
+        assert!(!paths.rules.join(".DS_Store").exists());
+    }
+    
+    #[test]
+    fn test_run_update_marks_conflicts() {
+        let temp_dir = TempDir::new().unwrap();
+        let source_dir = temp_dir.path().join("org");
+        fs::create_dir_all(source_dir.join("rules")).unwrap();
+        fs::write(source_dir.join("rules").join("00_style.md"), "Use tabs\n").unwrap();
+        
+        let paths = init_from(&source_dir, &temp_dir.path().join("project"));
+        fs::write(paths.rules.join("00_style.md"), "Use two spaces\n").unwrap();
+        fs::write(source_dir.join("rules").join("00_style.md"), "Use four spaces\n").unwrap();
+        
+        let result = ClinerUpdater::new(paths.clone(), UpdateOptions::default()).run_update();
+        
+        assert!(matches!(result, Err(ClinerError::MergeConflict(_))));
+        let content = fs::read_to_string(paths.rules.join("00_style.md")).unwrap();
+        assert!(content.contains("<<<<<<< local\nUse two spaces\n=======\nUse four spaces\n>>>>>>> upstream\n"));
+    }
+    
+    #[test]
+    fn test_run_update_dry_run_changes_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+        let source_dir = temp_dir.path().join("org");
+        fs::create_dir_all(source_dir.join("rules")).unwrap();
+        fs::write(source_dir.join("rules").join("00_style.md"), "v1\n").unwrap();
+        
+        let paths = init_from(&source_dir, &temp_dir.path().join("project"));
+        fs::write(source_dir.join("rules").join("00_style.md"), "v2\n").unwrap();
+        
+        let options = UpdateOptions { dry_run: true, ..UpdateOptions::default() };
+        let result = ClinerUpdater::new(paths.clone(), options).run_update();
+        
+        assert!(result.is_ok());
+        assert_eq!(fs::read_to_string(paths.rules.join("00_style.md")).unwrap(), "v1\n");
+        assert_eq!(fs::read_to_string(paths.snapshot_file(Path::new("rules/00_style.md"))).unwrap(), "v1\n");
+    }
+    
+    #[test]
+    fn test_run_update_without_lock_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        let paths = ClinePaths::from_root(temp_dir.path());
+        
+        let result = ClinerUpdater::new(paths, UpdateOptions::default()).run_update();
+        
+        assert!(result.is_err());
+    }
+}
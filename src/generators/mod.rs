@@ -2,8 +2,12 @@ mod output_generator;
 mod cliner_generator;
 mod cliner_initializer;
 mod cliner_runner;
+mod cliner_lister;
+mod cliner_updater;
 
 pub use output_generator::OutputGenerator;
 pub use cliner_generator::ClinerGenerator;
 pub use cliner_initializer::ClinerInitializer;
 pub use cliner_runner::ClinerRunner;
+pub use cliner_lister::ClinerLister;
+pub use cliner_updater::ClinerUpdater;
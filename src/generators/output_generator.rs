@@ -1,36 +1,109 @@
 use std::fs;
+use std::path::Path;
 use serde_json::Value;
 use crate::error::Result;
+use crate::models::OutputFormat;
+
+/// Top-level `.roomodes` field recording the profile or selection in JSON output.
+pub const GENERATED_BY_KEY: &str = "_generatedBy";
 
 pub struct OutputGenerator;
 
 impl OutputGenerator {
-    pub fn write_json_if_not_empty(json_values: Vec<Value>, output_path: &str, success_message: &str) -> Result<()> {
+    /// Writes JSON, with `header` in a top-level `_generatedBy` field when given.
+    pub fn write_json_if_not_empty<P: AsRef<Path>>(
+        json_values: Vec<Value>,
+        output_path: P,
+        header: Option<&str>,
+        success_message: &str,
+    ) -> Result<()> {
+        let output_path = output_path.as_ref();
         if json_values.is_empty() {
-            println!("No valid modes found, skipping {} generation", output_path);
+            println!("No valid modes found, skipping {} generation", output_path.display());
             return Ok(());
         }
         
-        let custom_modes_obj = serde_json::json!({
-            "customModes": json_values
-        });
+        let formatted_json = Self::render_modes(json_values, OutputFormat::Json, header)?;
+        Self::write_file(output_path, formatted_json)?;
+        println!("{}", success_message);
+        Ok(())
+    }
+    
+    /// Writes YAML, starting with `header` as a comment line when given.
+    pub fn write_yaml_if_not_empty<P: AsRef<Path>>(
+        json_values: Vec<Value>,
+        output_path: P,
+        header: Option<&str>,
+        success_message: &str,
+    ) -> Result<()> {
+        let output_path = output_path.as_ref();
+        if json_values.is_empty() {
+            println!("No valid modes found, skipping {} generation", output_path.display());
+            return Ok(());
+        }
         
-        let formatted_json = serde_json::to_string_pretty(&custom_modes_obj)?;
-        fs::write(output_path, formatted_json)?;
+        let formatted_yaml = Self::render_modes(json_values, OutputFormat::Yaml, header)?;
+        Self::write_file(output_path, formatted_yaml)?;
         println!("{}", success_message);
         Ok(())
     }
     
-    pub fn write_content_if_not_empty(content: String, output_path: &str, success_message: &str) -> Result<()> {
+    pub fn write_modes_if_not_empty<P: AsRef<Path>>(
+        json_values: Vec<Value>,
+        output_path: P,
+        format: OutputFormat,
+        header: Option<&str>,
+        success_message: &str,
+    ) -> Result<()> {
+        match format {
+            OutputFormat::Json => Self::write_json_if_not_empty(json_values, output_path, header, success_message),
+            OutputFormat::Yaml => Self::write_yaml_if_not_empty(json_values, output_path, header, success_message),
+        }
+    }
+    
+    /// Serializes modes the way they are written to `.roomodes`. JSON has no comments, so
+    /// `header` goes in a `_generatedBy` field there, and in a comment line in YAML.
+    pub fn render_modes(json_values: Vec<Value>, format: OutputFormat, header: Option<&str>) -> Result<String> {
+        let mut custom_modes_obj = serde_json::Map::new();
+        if let (OutputFormat::Json, Some(header)) = (format, header) {
+            custom_modes_obj.insert(GENERATED_BY_KEY.to_string(), Value::String(header.to_string()));
+        }
+        custom_modes_obj.insert("customModes".to_string(), Value::Array(json_values));
+        
+        match format {
+            OutputFormat::Json => Ok(serde_json::to_string_pretty(&custom_modes_obj)?),
+            OutputFormat::Yaml => {
+                let mut formatted_yaml = serde_yaml::to_string(&custom_modes_obj)?;
+                if let Some(header) = header {
+                    formatted_yaml.insert_str(0, &format!("# {}\n", header));
+                }
+                Ok(formatted_yaml)
+            },
+        }
+    }
+    
+    pub fn write_content_if_not_empty<P: AsRef<Path>>(content: String, output_path: P, success_message: &str) -> Result<()> {
+        let output_path = output_path.as_ref();
         if content.is_empty() {
-            println!("No rules found, skipping {} generation", output_path);
+            println!("No rules found, skipping {} generation", output_path.display());
             return Ok(());
         }
         
-        fs::write(output_path, content)?;
+        Self::write_file(output_path, content)?;
         println!("{}", success_message);
         Ok(())
     }
+    
+    fn write_file(output_path: &Path, content: String) -> Result<()> {
+        if let Some(parent_dir) = output_path.parent()
+            && !parent_dir.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent_dir)?;
+        }
+        
+        fs::write(output_path, content)?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -38,7 +111,6 @@ mod tests {
     use super::*;
     use serde_json::json;
     use tempfile::tempdir;
-    use std::path::Path;
     use std::io::Read;
 
     #[test]
@@ -52,7 +124,7 @@ mod tests {
             json!({"name": "Test2", "value": 456})
         ];
         
-        let result = OutputGenerator::write_json_if_not_empty(json_values, output_path_str, "成功メッセージ");
+        let result = OutputGenerator::write_json_if_not_empty(json_values, output_path_str, None, "成功メッセージ");
         
         assert!(result.is_ok());
         assert!(output_path.exists());
@@ -71,12 +143,72 @@ mod tests {
     #[test]
     fn test_write_json_if_not_empty_with_empty_array() {
         let json_values: Vec<Value> = vec![];
-        let result = OutputGenerator::write_json_if_not_empty(json_values, "nonexistent_path.json", "成功メッセージ");
+        let result = OutputGenerator::write_json_if_not_empty(json_values, "nonexistent_path.json", None, "成功メッセージ");
         
         assert!(result.is_ok());
         assert!(!Path::new("nonexistent_path.json").exists());
     }
     
+    #[test]
+    fn test_write_yaml_if_not_empty_uses_block_scalars() {
+        let temp_dir = tempdir().unwrap();
+        let output_path = temp_dir.path().join("test_output.yaml");
+        let output_path_str = output_path.to_str().unwrap();
+        
+        let json_values = vec![
+            json!({"slug": "test", "role_definition": "# Title\n\nFirst line\nSecond line"})
+        ];
+        
+        let result = OutputGenerator::write_yaml_if_not_empty(json_values, output_path_str, None, "成功メッセージ");
+        
+        assert!(result.is_ok());
+        
+        let contents = fs::read_to_string(&output_path).unwrap();
+        assert!(contents.starts_with("customModes:"));
+        assert!(contents.contains("role_definition: |-\n"));
+        assert!(contents.contains("    First line\n    Second line"));
+        assert!(!contents.contains("\\n"));
+        
+        let parsed: Value = serde_yaml::from_str(&contents).unwrap();
+        assert_eq!(parsed["customModes"][0]["role_definition"], "# Title\n\nFirst line\nSecond line");
+    }
+    
+    #[test]
+    fn test_write_yaml_if_not_empty_with_header() {
+        let temp_dir = tempdir().unwrap();
+        let output_path = temp_dir.path().join("test_output.yaml");
+        
+        OutputGenerator::write_yaml_if_not_empty(vec![json!({"slug": "test"})], &output_path, Some("Generated by cliner"), "成功メッセージ").unwrap();
+        
+        let contents = fs::read_to_string(&output_path).unwrap();
+        assert!(contents.starts_with("# Generated by cliner\ncustomModes:"));
+        let parsed: Value = serde_yaml::from_str(&contents).unwrap();
+        assert_eq!(parsed["customModes"][0]["slug"], "test");
+    }
+    
+    #[test]
+    fn test_render_modes_json_with_header() {
+        let rendered = OutputGenerator::render_modes(vec![json!({"slug": "test"})], OutputFormat::Json, Some("Generated by cliner with profile: ci")).unwrap();
+        
+        assert!(rendered.starts_with("{\n  \"_generatedBy\": \"Generated by cliner with profile: ci\",\n  \"customModes\": ["));
+        let unlabeled = OutputGenerator::render_modes(vec![json!({"slug": "test"})], OutputFormat::Json, None).unwrap();
+        assert!(!unlabeled.contains(GENERATED_BY_KEY));
+    }
+    
+    #[test]
+    fn test_write_modes_if_not_empty_json_matches_write_json() {
+        let temp_dir = tempdir().unwrap();
+        let direct_path = temp_dir.path().join("direct.json");
+        let dispatched_path = temp_dir.path().join("dispatched.json");
+        
+        let json_values = vec![json!({"slug": "test", "role_definition": "line1\nline2"})];
+        
+        OutputGenerator::write_json_if_not_empty(json_values.clone(), direct_path.to_str().unwrap(), Some("header"), "成功メッセージ").unwrap();
+        OutputGenerator::write_modes_if_not_empty(json_values, dispatched_path.to_str().unwrap(), OutputFormat::Json, Some("header"), "成功メッセージ").unwrap();
+        
+        assert_eq!(fs::read(direct_path).unwrap(), fs::read(dispatched_path).unwrap());
+    }
+    
     #[test]
     fn test_write_content_if_not_empty_with_content() {
         let temp_dir = tempdir().unwrap();
@@ -94,6 +226,17 @@ mod tests {
         assert_eq!(file_content, "Test\nMultiple lines");
     }
     
+    #[test]
+    fn test_write_content_creates_parent_directories() {
+        let temp_dir = tempdir().unwrap();
+        let output_path = temp_dir.path().join("build").join("nested").join(".clinerules");
+        
+        let result = OutputGenerator::write_content_if_not_empty("rules".to_string(), &output_path, "成功メッセージ");
+        
+        assert!(result.is_ok());
+        assert_eq!(fs::read_to_string(output_path).unwrap(), "rules");
+    }
+    
     #[test]
     fn test_write_content_if_not_empty_with_empty_string() {
         let content = "".to_string();
@@ -2,10 +2,17 @@ mod models;
 mod error;
 mod processors;
 mod generators;
+mod templates;
 
-use error::Result;
+use std::process::ExitCode;
 use generators::ClinerRunner;
 
-fn main() -> Result<()> {
-   ClinerRunner::run()
+fn main() -> ExitCode {
+    match ClinerRunner::run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            ExitCode::FAILURE
+        },
+    }
 }
@@ -0,0 +1,12 @@
+use serde::Deserialize;
+
+/// What `generate` and `check` do when `.clinerules` is over the token budget.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum BudgetAction {
+    #[default]
+    Warn,
+    Fail,
+    /// Leave out the lowest-priority rules until the output fits
+    Trim,
+}
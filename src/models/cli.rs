@@ -1,14 +1,295 @@
+use std::path::PathBuf;
 use clap::{Parser, Subcommand};
+use clap::builder::PossibleValuesParser;
+use crate::models::{ConflictPolicy, OutputFormat, OutputTarget, TagExpression, parse_output_override, parse_tag_expression};
+use crate::templates::TEMPLATE_NAMES;
 
 #[derive(Parser, Debug)]
 #[command(version, about = "A command line tool for managing Cline rules and modes")]
 pub struct Cli {
+    /// Project root containing .cline (defaults to searching upwards from the current directory)
+    #[arg(long, global = true)]
+    pub root: Option<PathBuf>,
+
+    /// Ignore modes and rules from the global config directories
+    #[arg(long, global = true)]
+    pub no_global: bool,
+
+    /// Use this global config directory instead of searching $CLINER_HOME, $HOME and $XDG_CONFIG_HOME; may be repeated
+    #[arg(long = "global-dir", value_name = "DIR", global = true)]
+    pub global_dirs: Vec<PathBuf>,
+
+    /// Also print files in the mode and rule directories that were skipped
+    #[arg(long, short, global = true)]
+    pub verbose: bool,
+
     #[command(subcommand)]
     pub command: Option<Commands>,
 }
 
 #[derive(Subcommand, Debug)]
 pub enum Commands {
-    Init,
-    Generate,
+    Init(InitArgs),
+    Generate(GenerateArgs),
+    /// Check that the generated files match their sources, without writing anything
+    Check(GenerateArgs),
+    /// Report the approximate token count of every rule, every mode and each output
+    Stats(GenerateArgs),
+    /// Merge upstream changes to the modes and rules copied by `init` into the project
+    Update(UpdateArgs),
+    /// List modes and rules with the layer each one comes from
+    List(ListArgs),
+    /// Print the project context rule generated from the manifests and directory layout
+    Context(ContextArgs),
+    /// Inspect cliner's configuration
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommands {
+    /// Print the resolved global config search order and source directories
+    Paths,
+}
+
+#[derive(clap::Args, Debug, Default)]
+pub struct InitArgs {
+    /// What to do when a file being copied already exists with different content
+    #[arg(long, value_enum, default_value_t = ConflictPolicy::Skip)]
+    pub on_conflict: ConflictPolicy,
+    /// Report what would be copied without changing any files
+    #[arg(long)]
+    pub dry_run: bool,
+    /// Start from a built-in template instead of the global config directories
+    #[arg(long, value_name = "NAME", value_parser = PossibleValuesParser::new(TEMPLATE_NAMES), conflicts_with = "from")]
+    pub template: Option<String>,
+    /// Copy modes and rules from a directory or git repository instead of the global config directories
+    #[arg(long, value_name = "PATH_OR_URL")]
+    pub from: Option<String>,
+    /// Symlink files from the global config directories instead of copying them
+    #[arg(long, conflicts_with_all = ["template", "from"])]
+    pub link: bool,
+    /// Branch, tag or commit to check out when --from is a git repository
+    #[arg(long = "ref", value_name = "REF", requires = "from")]
+    pub reference: Option<String>,
+    /// List the built-in templates and exit
+    #[arg(long)]
+    pub list_templates: bool,
+}
+
+#[derive(clap::Args, Debug, Default)]
+pub struct UpdateArgs {
+    /// Report what would change without changing any files
+    #[arg(long)]
+    pub dry_run: bool,
+    /// Branch, tag or commit to update to instead of the one recorded at init
+    #[arg(long = "ref", value_name = "REF")]
+    pub reference: Option<String>,
+}
+
+/// Flags for `generate`. Each one overrides the matching setting in `.cline/cliner.toml`.
+#[derive(clap::Args, Debug, Default)]
+pub struct GenerateArgs {
+    /// Output format for .roomodes [config: output.format]
+    #[arg(long, value_enum)]
+    pub format: Option<OutputFormat>,
+    /// Merge into an existing .roomodes, keeping modes not generated by cliner [config: output.merge]
+    #[arg(long, overrides_with = "no_merge")]
+    pub merge: bool,
+    /// Rewrite .roomodes from scratch even if output.merge is set
+    #[arg(long, overrides_with = "merge")]
+    pub no_merge: bool,
+    /// Only generate the given target; may be repeated [config: output.targets]
+    #[arg(long = "target", value_name = "TARGET")]
+    pub targets: Vec<OutputTarget>,
+    /// Override an output path, e.g. `--out roomodes=build/.roomodes` [config: output.roomodes, output.clinerules]
+    #[arg(long, value_name = "TARGET=PATH", value_parser = parse_output_override)]
+    pub out: Vec<(OutputTarget, PathBuf)>,
+    /// Fail on unreadable or invalid source files instead of skipping them [config: sources.strict]
+    #[arg(long, overrides_with = "no_strict")]
+    pub strict: bool,
+    /// Skip unreadable or invalid source files even if sources.strict is set
+    #[arg(long, overrides_with = "strict")]
+    pub no_strict: bool,
+    #[command(flatten)]
+    pub selection: SelectionArgs,
+}
+
+impl GenerateArgs {
+    /// `Some` when `--merge` or `--no-merge` was given; the last one wins.
+    pub fn merge_flag(&self) -> Option<bool> {
+        Self::flag(self.merge, self.no_merge)
+    }
+    
+    /// `Some` when `--strict` or `--no-strict` was given; the last one wins.
+    pub fn strict_flag(&self) -> Option<bool> {
+        Self::flag(self.strict, self.no_strict)
+    }
+    
+    fn flag(on: bool, off: bool) -> Option<bool> {
+        match (on, off) {
+            (true, _) => Some(true),
+            (_, true) => Some(false),
+            _ => None,
+        }
+    }
+}
+
+#[derive(clap::Args, Debug, Default)]
+pub struct ListArgs {
+    #[command(flatten)]
+    pub selection: SelectionArgs,
+}
+
+#[derive(clap::Args, Debug, Default)]
+pub struct ContextArgs {
+    /// How many directory levels the layout shows [config: context.depth]
+    #[arg(long)]
+    pub depth: Option<usize>,
+}
+
+#[derive(clap::Args, Debug, Default, Clone)]
+pub struct SelectionArgs {
+    /// Use a named profile's selection and group changes [config: profiles]
+    #[arg(long, value_name = "NAME")]
+    pub profile: Option<String>,
+    /// Only use rules and modes whose tags match, e.g. `--select 'rust && !legacy'`
+    #[arg(long, value_name = "EXPR", value_parser = parse_tag_expression)]
+    pub select: Option<TagExpression>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negated_flags() {
+        let parse = |args: &[&str]| match Cli::try_parse_from(args).unwrap().command {
+            Some(Commands::Generate(args)) => (args.merge_flag(), args.strict_flag()),
+            other => panic!("Expected Generate command, got {:?}", other),
+        };
+        
+        assert_eq!(parse(&["cliner", "generate"]), (None, None));
+        assert_eq!(parse(&["cliner", "generate", "--no-merge", "--strict"]), (Some(false), Some(true)));
+        assert_eq!(parse(&["cliner", "generate", "--strict", "--no-strict", "--no-merge", "--merge"]), (Some(true), Some(false)));
+    }
+
+    #[test]
+    fn test_parse_generate_flags() {
+        let cli = Cli::try_parse_from([
+            "cliner", "--root", "project", "generate", "--format", "yaml", "--out", "clinerules=out/rules.md",
+            "--target", "clinerules", "--strict",
+        ]).unwrap();
+
+        assert_eq!(cli.root, Some(PathBuf::from("project")));
+        assert!(!cli.verbose);
+        match cli.command {
+            Some(Commands::Generate(args)) => {
+                assert_eq!(args.format, Some(OutputFormat::Yaml));
+                assert!(!args.merge);
+                assert!(args.strict);
+                assert_eq!(args.targets, vec![OutputTarget::Clinerules]);
+                assert_eq!(args.out, vec![(OutputTarget::Clinerules, PathBuf::from("out/rules.md"))]);
+            },
+            other => panic!("Expected Generate command, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_list_with_no_global() {
+        let cli = Cli::try_parse_from(["cliner", "list", "--no-global", "--verbose"]).unwrap();
+
+        assert!(cli.no_global);
+        assert!(cli.verbose);
+        assert!(matches!(cli.command, Some(Commands::List(_))));
+    }
+
+    #[test]
+    fn test_parse_select() {
+        let cli = Cli::try_parse_from(["cliner", "list", "--select", "security || api"]).unwrap();
+        match cli.command {
+            Some(Commands::List(args)) => assert_eq!(args.selection.select.unwrap().to_string(), "security || api"),
+            other => panic!("Expected List command, got {:?}", other),
+        }
+
+        let message = Cli::try_parse_from(["cliner", "generate", "--select", "rust &&"]).unwrap_err().to_string();
+        assert!(message.contains("invalid tag expression 'rust &&': expected a tag at column 8"));
+    }
+
+    #[test]
+    fn test_parse_config_paths_with_global_dirs() {
+        let cli = Cli::try_parse_from(["cliner", "config", "paths", "--global-dir", "/a", "--global-dir", "/b"]).unwrap();
+
+        assert_eq!(cli.global_dirs, vec![PathBuf::from("/a"), PathBuf::from("/b")]);
+        assert!(matches!(cli.command, Some(Commands::Config { command: ConfigCommands::Paths })));
+    }
+
+    #[test]
+    fn test_parse_init_flags() {
+        let cli = Cli::try_parse_from(["cliner", "init", "--on-conflict", "backup", "--dry-run"]).unwrap();
+
+        match cli.command {
+            Some(Commands::Init(args)) => {
+                assert_eq!(args.on_conflict, ConflictPolicy::Backup);
+                assert!(args.dry_run);
+            },
+            other => panic!("Expected Init command, got {:?}", other),
+        }
+
+        let cli = Cli::try_parse_from(["cliner", "init"]).unwrap();
+        match cli.command {
+            Some(Commands::Init(args)) => {
+                assert_eq!(args.on_conflict, ConflictPolicy::Skip);
+                assert!(!args.dry_run);
+                assert_eq!(args.template, None);
+            },
+            other => panic!("Expected Init command, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_init_template() {
+        let cli = Cli::try_parse_from(["cliner", "init", "--template", "rust"]).unwrap();
+        assert!(matches!(cli.command, Some(Commands::Init(InitArgs { template: Some(ref name), .. })) if name == "rust"));
+
+        assert!(Cli::try_parse_from(["cliner", "init", "--template", "cobol"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_init_from() {
+        let cli = Cli::try_parse_from(["cliner", "init", "--from", "file:///srv/rules.git", "--ref", "v1"]).unwrap();
+
+        match cli.command {
+            Some(Commands::Init(args)) => {
+                assert_eq!(args.from.as_deref(), Some("file:///srv/rules.git"));
+                assert_eq!(args.reference.as_deref(), Some("v1"));
+            },
+            other => panic!("Expected Init command, got {:?}", other),
+        }
+
+        assert!(Cli::try_parse_from(["cliner", "init", "--ref", "v1"]).is_err());
+        assert!(Cli::try_parse_from(["cliner", "init", "--from", "x", "--template", "rust"]).is_err());
+        assert!(Cli::try_parse_from(["cliner", "init", "--from", "x", "--link"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_update_flags() {
+        let cli = Cli::try_parse_from(["cliner", "update", "--dry-run", "--ref", "v2"]).unwrap();
+
+        match cli.command {
+            Some(Commands::Update(args)) => {
+                assert!(args.dry_run);
+                assert_eq!(args.reference.as_deref(), Some("v2"));
+            },
+            other => panic!("Expected Update command, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_out() {
+        assert!(Cli::try_parse_from(["cliner", "generate", "--out", "cursor=x"]).is_err());
+        assert!(Cli::try_parse_from(["cliner", "generate", "--target", "cursor"]).is_err());
+    }
 }
@@ -0,0 +1,104 @@
+use crate::error::{ClinerError, Result};
+use crate::models::tag_expression::{BooleanExpression, Parser, Token};
+
+/// The expression of an `<!-- @if ... -->` block, e.g. `target == "clinerules" && !env.CI`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Condition {
+    Defined(String),
+    Equals(String, String),
+    NotEquals(String, String),
+    Not(Box<Condition>),
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
+}
+
+impl Condition {
+    pub fn parse(input: &str) -> Result<Self> {
+        Parser::parse(input).map_err(ClinerError::InvalidFormat)
+    }
+    
+    pub fn evaluate(&self, value_of: &dyn Fn(&str) -> Option<String>) -> bool {
+        match self {
+            Condition::Defined(name) => value_of(name).is_some_and(|value| !value.is_empty()),
+            Condition::Equals(name, expected) => value_of(name).as_ref() == Some(expected),
+            Condition::NotEquals(name, expected) => value_of(name).as_ref() != Some(expected),
+            Condition::Not(inner) => !inner.evaluate(value_of),
+            Condition::And(left, right) => left.evaluate(value_of) && right.evaluate(value_of),
+            Condition::Or(left, right) => left.evaluate(value_of) || right.evaluate(value_of),
+        }
+    }
+}
+
+impl BooleanExpression for Condition {
+    const KIND: &'static str = "condition";
+    const NAME: &'static str = "name";
+    
+    fn name(parser: &mut Parser<'_>, name: String) -> std::result::Result<Self, String> {
+        let operator = if parser.next_if(&Token::Equals) {
+            "=="
+        } else if parser.next_if(&Token::NotEquals) {
+            "!="
+        } else {
+            return Ok(Condition::Defined(name));
+        };
+        
+        let column = parser.column();
+        match parser.next() {
+            Some((_, Token::Text(text))) if operator == "==" => Ok(Condition::Equals(name, text.clone())),
+            Some((_, Token::Text(text))) => Ok(Condition::NotEquals(name, text.clone())),
+            _ => Err(parser.error(column, &format!("expected a quoted string after '{}' {}", name, operator))),
+        }
+    }
+    
+    fn not(inner: Self) -> Self {
+        Condition::Not(Box::new(inner))
+    }
+    
+    fn and(left: Self, right: Self) -> Self {
+        Condition::And(Box::new(left), Box::new(right))
+    }
+    
+    fn or(left: Self, right: Self) -> Self {
+        Condition::Or(Box::new(left), Box::new(right))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn values(name: &str) -> Option<String> {
+        match name {
+            "target" => Some("clinerules".to_string()),
+            "profile" => Some("ci".to_string()),
+            "empty" => Some(String::new()),
+            _ => None,
+        }
+    }
+    
+    #[test]
+    fn test_evaluate() {
+        let evaluate = |input: &str| Condition::parse(input).unwrap().evaluate(&values);
+        
+        assert!(evaluate("target == \"clinerules\""));
+        assert!(!evaluate("target == \"cursor\""));
+        assert!(evaluate("target != \"cursor\" && profile == \"ci\""));
+        assert!(evaluate("!(profile == \"dev\") || missing"));
+        assert!(evaluate("profile"));
+        assert!(!evaluate("missing"));
+        assert!(!evaluate("empty"));
+        assert!(evaluate("missing != \"x\""));
+    }
+    
+    #[test]
+    fn test_parse_errors() {
+        let message = |input: &str| Condition::parse(input).unwrap_err().to_string();
+        
+        assert!(message("target = \"x\"").contains("expected '=='"));
+        assert!(message("target == cursor").contains("expected a quoted string after 'target' =="));
+        assert!(message("target == \"x").contains("unclosed string"));
+        assert!(message("(profile").contains("expected ')'"));
+        assert!(message("").contains("expected a name at column 1"));
+        assert!(message("profile target").contains("unexpected name 'target' at column 9"));
+    }
+}
@@ -0,0 +1,422 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use serde::Deserialize;
+use crate::error::{ClinerError, Result};
+use crate::models::{BudgetAction, OutputFormat, OutputTarget, SortOrder, SourceFilter, TagExpression, TokenEstimator, UndefinedPolicy};
+use crate::models::source_filter::DEFAULT_INCLUDE;
+
+pub const CONFIG_FILE_NAME: &str = "cliner.toml";
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClinerConfig {
+    pub sources: SourcesConfig,
+    pub output: OutputConfig,
+    pub layers: LayersConfig,
+    /// Named selections of rules and modes, chosen with `generate --profile`
+    pub profiles: BTreeMap<String, ProfileConfig>,
+    /// Values for `{{name}}` in rules and modes, on top of the built-in variables
+    pub variables: BTreeMap<String, String>,
+    pub templates: TemplatesConfig,
+    /// Commands whose output `{{command:NAME}}` may insert; nothing else is ever run
+    pub commands: BTreeMap<String, CommandConfig>,
+    pub context: ContextConfig,
+    pub tokens: TokensConfig,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct SourcesConfig {
+    /// Mode directory, relative to `.cline`
+    pub modes: PathBuf,
+    /// Rule directory, relative to `.cline`
+    pub rules: PathBuf,
+    pub sort: SortOrder,
+    /// Fail on unreadable or invalid source files instead of skipping them
+    pub strict: bool,
+    /// Globs a file must match, relative to the mode or rule directory
+    pub include: Vec<String>,
+    /// Globs for files to leave out even if they match `include`
+    pub exclude: Vec<String>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutputConfig {
+    pub targets: Vec<OutputTarget>,
+    pub format: OutputFormat,
+    pub merge: bool,
+    /// Output path for .roomodes, relative to the project root
+    pub roomodes: Option<PathBuf>,
+    /// Output path for .clinerules, relative to the project root
+    pub clinerules: Option<PathBuf>,
+    /// Text appended after every rule in .clinerules
+    pub rule_separator: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct LayersConfig {
+    /// Read modes and rules from the global config directories at generate time instead of
+    /// copying them at `init`. Off by default, so outputs don't depend on each machine's files
+    pub global: bool,
+    /// Directory of an organization pack with `modes/` and `rules/`, relative to the project root
+    pub organization: Option<PathBuf>,
+    /// Include the per-user override layer in `.cline/local`
+    pub local: bool,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct TemplatesConfig {
+    /// What to do with a `{{variable}}` that has no value
+    pub undefined: UndefinedPolicy,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct ContextConfig {
+    /// Start .clinerules with a rule describing the project's language, build commands and layout
+    pub enabled: bool,
+    /// How many directory levels the layout shows
+    pub depth: usize,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct TokensConfig {
+    pub estimator: TokenEstimator,
+    pub chars_per_token: f64,
+    pub tokens_per_word: f64,
+    /// Approximate tokens `.clinerules` may use
+    pub budget: Option<usize>,
+    pub on_exceed: BudgetAction,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct CommandConfig {
+    /// Program and arguments, run from the project root without a shell
+    pub run: Vec<String>,
+    #[serde(default = "CommandConfig::default_timeout_secs")]
+    pub timeout_secs: u64,
+    #[serde(default = "CommandConfig::default_max_output_bytes")]
+    pub max_output_bytes: usize,
+}
+
+impl CommandConfig {
+    fn default_timeout_secs() -> u64 {
+        10
+    }
+    
+    fn default_max_output_bytes() -> usize {
+        64 * 1024
+    }
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProfileConfig {
+    /// Tag expression that rules and modes must match, e.g. `"!internal && !experimental"`
+    pub select: Option<String>,
+    /// Tool groups removed from every generated mode
+    pub remove_groups: Vec<String>,
+    /// Tool groups added to every generated mode that lacks them
+    pub add_groups: Vec<String>,
+    /// Replaces `tokens.budget` for this profile
+    pub budget: Option<usize>,
+    /// Replaces `tokens.on_exceed` for this profile
+    pub on_exceed: Option<BudgetAction>,
+}
+
+impl Default for SourcesConfig {
+    fn default() -> Self {
+        Self {
+            modes: PathBuf::from("modes"),
+            rules: PathBuf::from("rules"),
+            sort: SortOrder::default(),
+            strict: false,
+            include: vec![DEFAULT_INCLUDE.to_string()],
+            exclude: Vec::new(),
+        }
+    }
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        Self {
+            targets: OutputTarget::ALL.to_vec(),
+            format: OutputFormat::default(),
+            merge: false,
+            roomodes: None,
+            clinerules: None,
+            rule_separator: "\n".to_string(),
+        }
+    }
+}
+
+impl Default for ContextConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            depth: 2,
+        }
+    }
+}
+
+impl Default for TokensConfig {
+    fn default() -> Self {
+        Self {
+            estimator: TokenEstimator::default(),
+            chars_per_token: 4.0,
+            tokens_per_word: 1.3,
+            budget: None,
+            on_exceed: BudgetAction::default(),
+        }
+    }
+}
+
+impl TokensConfig {
+    /// Approximate number of tokens in `text`, rounded up.
+    pub fn count(&self, text: &str) -> usize {
+        let estimate = match self.estimator {
+            TokenEstimator::Chars => text.chars().count() as f64 / self.chars_per_token,
+            TokenEstimator::Words => text.split_whitespace().count() as f64 * self.tokens_per_word,
+        };
+        estimate.ceil() as usize
+    }
+}
+
+impl Default for LayersConfig {
+    fn default() -> Self {
+        Self {
+            global: false,
+            organization: None,
+            local: true,
+        }
+    }
+}
+
+impl ClinerConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        
+        let content = fs::read_to_string(path)?;
+        Self::parse(&content, path)
+    }
+    
+    pub fn parse(content: &str, path: &Path) -> Result<Self> {
+        let config: ClinerConfig = toml::from_str(content)
+            .map_err(|err| ClinerError::Config(format!("{}: {}", path.display(), err)))?;
+        
+        config.validate(path)?;
+        Ok(config)
+    }
+    
+    fn validate(&self, path: &Path) -> Result<()> {
+        if self.output.targets.is_empty() {
+            return Err(ClinerError::Config(format!("{}: output.targets must not be empty", path.display())));
+        }
+        
+        let mut seen_targets = HashSet::new();
+        for target in &self.output.targets {
+            if !seen_targets.insert(target) {
+                return Err(ClinerError::Config(format!("{}: output.targets lists '{}' more than once", path.display(), target)));
+            }
+        }
+        
+        for (key, dir) in [("sources.modes", &self.sources.modes), ("sources.rules", &self.sources.rules)] {
+            let leaves_base = dir.components().any(|component| !matches!(component, Component::Normal(_) | Component::CurDir));
+            if dir.as_os_str().is_empty() || leaves_base {
+                return Err(ClinerError::Config(format!("{}: {} must be a relative path inside .cline", path.display(), key)));
+            }
+        }
+        
+        for (key, patterns) in [("sources.include", &self.sources.include), ("sources.exclude", &self.sources.exclude)] {
+            if let Err(err) = SourceFilter::build_glob_set(patterns) {
+                return Err(ClinerError::Config(format!("{}: {}: {}", path.display(), key, err)));
+            }
+        }
+        
+        for (name, profile) in &self.profiles {
+            if let Some(select) = &profile.select
+                && let Err(err) = TagExpression::parse(select)
+            {
+                return Err(ClinerError::Config(format!("{}: profiles.{}.select: {}", path.display(), name, err)));
+            }
+        }
+        
+        for (name, command) in &self.commands {
+            if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') {
+                return Err(ClinerError::Config(format!("{}: commands.{}: names may only use letters, digits, '-' and '_'", path.display(), name)));
+            }
+            if command.run.is_empty() {
+                return Err(ClinerError::Config(format!("{}: commands.{}.run must name a program", path.display(), name)));
+            }
+        }
+        
+        for (key, ratio) in [("tokens.chars_per_token", self.tokens.chars_per_token), ("tokens.tokens_per_word", self.tokens.tokens_per_word)] {
+            if !(ratio.is_finite() && ratio > 0.0) {
+                return Err(ClinerError::Config(format!("{}: {} must be a positive number", path.display(), key)));
+            }
+        }
+        
+        Ok(())
+    }
+    
+    pub fn profile(&self, name: &str) -> Result<&ProfileConfig> {
+        self.profiles.get(name).ok_or_else(|| {
+            let defined: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            let defined = if defined.is_empty() { "none".to_string() } else { defined.join(", ") };
+            ClinerError::Config(format!("Unknown profile '{}'; defined profiles: {}", name, defined))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+    
+    fn parse(content: &str) -> Result<ClinerConfig> {
+        ClinerConfig::parse(content, Path::new(".cline/cliner.toml"))
+    }
+    
+    #[test]
+    fn test_missing_file_uses_defaults() {
+        let temp_dir = tempdir().unwrap();
+        let config = ClinerConfig::load(&temp_dir.path().join(CONFIG_FILE_NAME)).unwrap();
+        
+        assert_eq!(config.sources.modes, PathBuf::from("modes"));
+        assert_eq!(config.output.targets, OutputTarget::ALL.to_vec());
+        assert_eq!(config.output.format, OutputFormat::Json);
+        assert_eq!(config.output.rule_separator, "\n");
+        assert!(!config.sources.strict);
+        assert!(!config.layers.global);
+        assert!(config.layers.local);
+        assert!(!config.context.enabled);
+        assert_eq!(config.context.depth, 2);
+    }
+    
+    #[test]
+    fn test_parse_full_config() {
+        let config = parse(r#"
+[sources]
+modes = "agent-modes"
+rules = "agent-rules"
+sort = "case-insensitive"
+strict = true
+
+[output]
+targets = ["clinerules"]
+format = "yaml"
+merge = true
+roomodes = "build/.roomodes"
+clinerules = "build/.clinerules"
+rule_separator = "\n---\n"
+
+[layers]
+global = true
+organization = "../org-pack"
+local = false
+"#).unwrap();
+        
+        assert_eq!(config.sources.modes, PathBuf::from("agent-modes"));
+        assert_eq!(config.sources.sort, SortOrder::CaseInsensitive);
+        assert!(config.sources.strict);
+        assert_eq!(config.output.targets, vec![OutputTarget::Clinerules]);
+        assert_eq!(config.output.format, OutputFormat::Yaml);
+        assert!(config.output.merge);
+        assert_eq!(config.output.roomodes, Some(PathBuf::from("build/.roomodes")));
+        assert_eq!(config.output.rule_separator, "\n---\n");
+        assert!(config.layers.global);
+        assert_eq!(config.layers.organization, Some(PathBuf::from("../org-pack")));
+        assert!(!config.layers.local);
+    }
+    
+    #[test]
+    fn test_source_globs() {
+        let config = parse("[sources]\ninclude = [\"*.md\", \"*.txt\"]\nexclude = [\"drafts/**\"]\n").unwrap();
+        assert_eq!(config.sources.include, vec!["*.md", "*.txt"]);
+        assert_eq!(config.sources.exclude, vec!["drafts/**"]);
+        assert_eq!(ClinerConfig::default().sources.include, vec!["*.md"]);
+        
+        let message = parse("[sources]\nexclude = [\"[\"]\n").unwrap_err().to_string();
+        assert!(message.contains("sources.exclude"));
+    }
+    
+    #[test]
+    fn test_variables() {
+        let config = parse("[variables]\nteam = \"platform\"\n\"project.name\" = \"Acme\"\n\n[templates]\nundefined = \"keep\"\n").unwrap();
+        
+        assert_eq!(config.variables["team"], "platform");
+        assert_eq!(config.variables["project.name"], "Acme");
+        assert_eq!(config.templates.undefined, UndefinedPolicy::Keep);
+        assert_eq!(ClinerConfig::default().templates.undefined, UndefinedPolicy::Error);
+    }
+    
+    #[test]
+    fn test_commands() {
+        let config = parse("[commands.members]\nrun = [\"cargo\", \"metadata\"]\ntimeout_secs = 5\n").unwrap();
+        
+        let command = &config.commands["members"];
+        assert_eq!(command.run, vec!["cargo", "metadata"]);
+        assert_eq!(command.timeout_secs, 5);
+        assert_eq!(command.max_output_bytes, 64 * 1024);
+        
+        assert!(parse("[commands.members]\nrun = []\n").unwrap_err().to_string().contains("commands.members.run"));
+        assert!(parse("[commands.members]\ntimeout_secs = 5\n").is_err());
+    }
+    
+    #[test]
+    fn test_tokens() {
+        let config = parse("[tokens]\nestimator = \"words\"\ntokens_per_word = 2.0\nbudget = 8000\non_exceed = \"fail\"\n").unwrap();
+        
+        assert_eq!(config.tokens.budget, Some(8000));
+        assert_eq!(config.tokens.on_exceed, BudgetAction::Fail);
+        assert_eq!(config.tokens.count("three short words"), 6);
+        assert_eq!(ClinerConfig::default().tokens.count("ten chars!"), 3);
+        assert_eq!(ClinerConfig::default().tokens.count(""), 0);
+        
+        assert!(parse("[tokens]\nchars_per_token = 0\n").unwrap_err().to_string().contains("tokens.chars_per_token"));
+    }
+    
+    #[test]
+    fn test_profiles() {
+        let config = parse("[profiles.ci]\nselect = \"!local-only\"\nremove_groups = [\"command\"]\n").unwrap();
+        
+        let profile = config.profile("ci").unwrap();
+        assert_eq!(profile.select.as_deref(), Some("!local-only"));
+        assert_eq!(profile.remove_groups, vec!["command"]);
+        assert_eq!(profile.budget, None);
+        assert!(config.profile("release").unwrap_err().to_string().contains("defined profiles: ci"));
+        
+        let message = parse("[profiles.ci]\nselect = \"a &&\"\n").unwrap_err().to_string();
+        assert!(message.contains("profiles.ci.select"));
+    }
+    
+    #[test]
+    fn test_unknown_keys_are_rejected() {
+        let err = parse("[output]\nformats = \"yaml\"\n").unwrap_err();
+        let message = err.to_string();
+        
+        assert!(message.contains(".cline/cliner.toml"));
+        assert!(message.contains("unknown field `formats`"));
+        
+        assert!(parse("[extras]\nkey = 1\n").is_err());
+    }
+    
+    #[test]
+    fn test_invalid_values_are_rejected() {
+        assert!(parse("[output]\nformat = \"xml\"\n").is_err());
+        assert!(parse("[output]\ntargets = []\n").is_err());
+        assert!(parse("[output]\ntargets = [\"roomodes\", \"roomodes\"]\n").is_err());
+        assert!(parse("[sources]\nrules = \"/etc/rules\"\n").is_err());
+        assert!(parse("[sources]\nmodes = \"../modes\"\n").is_err());
+        assert!(parse("[sources]\nrules = \"shared/../../rules\"\n").is_err());
+        assert!(parse("[sources]\nrules = \"./team/rules\"\n").is_ok());
+    }
+}
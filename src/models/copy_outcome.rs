@@ -0,0 +1,86 @@
+use std::fmt;
+use std::path::PathBuf;
+use clap::ValueEnum;
+
+/// What `init` does when a destination file already exists with different content.
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Keep the existing file
+    #[default]
+    Skip,
+    /// Replace the existing file
+    Overwrite,
+    /// Rename the existing file to `<name>.bak` and copy the new one
+    Backup,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CopyAction {
+    Copied,
+    Linked,
+    Unchanged,
+    Skipped,
+    Overwritten,
+    BackedUp(PathBuf),
+}
+
+impl CopyAction {
+    pub fn label(&self) -> &'static str {
+        match self {
+            CopyAction::Copied => "copy",
+            CopyAction::Linked => "link",
+            CopyAction::Unchanged => "unchanged",
+            CopyAction::Skipped => "skip",
+            CopyAction::Overwritten => "overwrite",
+            CopyAction::BackedUp(_) => "backup",
+        }
+    }
+    
+    pub fn changes_destination(&self) -> bool {
+        !matches!(self, CopyAction::Unchanged | CopyAction::Skipped)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CopyOutcome {
+    pub source: PathBuf,
+    pub destination: PathBuf,
+    pub action: CopyAction,
+}
+
+impl fmt::Display for CopyOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:<10} {}", self.action.label(), self.destination.display())?;
+        
+        match &self.action {
+            CopyAction::Skipped => write!(f, " (exists with different content)"),
+            CopyAction::BackedUp(backup_path) => write!(f, " (previous version saved as {})", backup_path.display()),
+            _ => write!(f, " (from {})", self.source.display()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_display() {
+        let outcome = CopyOutcome {
+            source: PathBuf::from("/global/rules/a.md"),
+            destination: PathBuf::from(".cline/rules/a.md"),
+            action: CopyAction::BackedUp(PathBuf::from(".cline/rules/a.md.bak")),
+        };
+        
+        assert_eq!(outcome.to_string(), "backup     .cline/rules/a.md (previous version saved as .cline/rules/a.md.bak)");
+    }
+    
+    #[test]
+    fn test_changes_destination() {
+        assert!(CopyAction::Copied.changes_destination());
+        assert!(CopyAction::Linked.changes_destination());
+        assert!(CopyAction::Overwritten.changes_destination());
+        assert!(!CopyAction::Skipped.changes_destination());
+        assert!(!CopyAction::Unchanged.changes_destination());
+    }
+}
@@ -0,0 +1,240 @@
+use std::collections::BTreeMap;
+use crate::error::Result;
+use crate::models::{ClinerConfig, CommandConfig, GenerateArgs, OutputFormat, OutputTarget, SelectionArgs, SortOrder, SourceFilter, TagExpression, TokensConfig, UndefinedPolicy};
+
+#[derive(Debug, Clone)]
+pub struct GenerateOptions {
+    pub format: OutputFormat,
+    pub merge: bool,
+    pub targets: Vec<OutputTarget>,
+    pub sort: SortOrder,
+    pub strict: bool,
+    pub rule_separator: String,
+    /// Print files that were skipped by the source filter
+    pub verbose: bool,
+    pub filter: SourceFilter,
+    /// Name of the profile in use, recorded in generated file headers
+    pub profile: Option<String>,
+    /// Tag expression rules and modes must match to be generated
+    pub select: Option<TagExpression>,
+    /// Tool groups removed from every generated mode
+    pub remove_groups: Vec<String>,
+    /// Tool groups added to every generated mode
+    pub add_groups: Vec<String>,
+    /// Config-defined values for `{{name}}`
+    pub variables: BTreeMap<String, String>,
+    pub undefined: UndefinedPolicy,
+    /// Commands `{{command:NAME}}` may run
+    pub commands: BTreeMap<String, CommandConfig>,
+    /// Use cached command output instead of running commands, as `check` does
+    pub cached_commands: bool,
+    /// Layout depth of the project context rule, or `None` if it isn't generated
+    pub context_depth: Option<usize>,
+    /// Token estimator and the budget for `.clinerules`
+    pub tokens: TokensConfig,
+}
+
+impl GenerateOptions {
+    pub fn from_config(config: &ClinerConfig) -> Self {
+        Self {
+            format: config.output.format,
+            merge: config.output.merge,
+            targets: config.output.targets.clone(),
+            sort: config.sources.sort,
+            strict: config.sources.strict,
+            rule_separator: config.output.rule_separator.clone(),
+            verbose: false,
+            // The globs were checked when the config was parsed; `.clineignore` is added by `with_filter`.
+            filter: SourceFilter::new(&config.sources.include, &config.sources.exclude, None).unwrap_or_default(),
+            profile: None,
+            select: None,
+            remove_groups: Vec::new(),
+            add_groups: Vec::new(),
+            variables: config.variables.clone(),
+            undefined: config.templates.undefined,
+            commands: config.commands.clone(),
+            cached_commands: false,
+            context_depth: config.context.enabled.then_some(config.context.depth),
+            tokens: config.tokens.clone(),
+        }
+    }
+    
+    /// Applies the selection and group changes of the profile called `name`.
+    pub fn with_profile(mut self, config: &ClinerConfig, name: &str) -> Result<Self> {
+        let profile = config.profile(name)?;
+        if let Some(select) = &profile.select {
+            self.select = TagExpression::both(self.select, Some(TagExpression::parse(select)?));
+        }
+        self.profile = Some(name.to_string());
+        self.remove_groups = profile.remove_groups.clone();
+        self.add_groups = profile.add_groups.clone();
+        if let Some(budget) = profile.budget {
+            self.tokens.budget = Some(budget);
+        }
+        if let Some(on_exceed) = profile.on_exceed {
+            self.tokens.on_exceed = on_exceed;
+        }
+        Ok(self)
+    }
+    
+    /// Applies `--profile`, then narrows the selection further with `--select`.
+    pub fn with_selection(mut self, config: &ClinerConfig, selection: &SelectionArgs) -> Result<Self> {
+        if let Some(profile) = &selection.profile {
+            self = self.with_profile(config, profile)?;
+        }
+        self.select = TagExpression::both(self.select, selection.select.clone());
+        Ok(self)
+    }
+    
+    /// Describes the profile and selection used, for the header of generated files.
+    pub fn selection_header(&self) -> Option<String> {
+        let mut parts = Vec::new();
+        if let Some(profile) = &self.profile {
+            parts.push(format!("profile: {}", profile));
+        }
+        if let Some(select) = &self.select {
+            parts.push(format!("select: {}", select));
+        }
+        
+        if parts.is_empty() {
+            return None;
+        }
+        Some(format!("Generated by cliner with {}", parts.join(", ")))
+    }
+    
+    pub fn with_filter(mut self, filter: SourceFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+    
+    /// Applies command line flags on top of the config file settings.
+    pub fn with_args(mut self, args: &GenerateArgs) -> Self {
+        if let Some(format) = args.format {
+            self.format = format;
+        }
+        if let Some(merge) = args.merge_flag() {
+            self.merge = merge;
+        }
+        if !args.targets.is_empty() {
+            self.targets = args.targets.clone();
+        }
+        if let Some(strict) = args.strict_flag() {
+            self.strict = strict;
+        }
+        self
+    }
+    
+    pub fn is_target_enabled(&self, target: OutputTarget) -> bool {
+        self.targets.contains(&target)
+    }
+    
+    pub fn is_selected(&self, tags: &[String]) -> bool {
+        self.select.as_ref().is_none_or(|select| select.matches(tags))
+    }
+}
+
+impl Default for GenerateOptions {
+    fn default() -> Self {
+        Self::from_config(&ClinerConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use crate::models::BudgetAction;
+    
+    #[test]
+    fn test_args_override_config() {
+        let config = ClinerConfig::parse(
+            "[output]\nformat = \"yaml\"\ntargets = [\"roomodes\"]\nrule_separator = \"\\n\\n\"\n",
+            Path::new("cliner.toml"),
+        ).unwrap();
+        let args = GenerateArgs {
+            format: Some(OutputFormat::Json),
+            targets: vec![OutputTarget::Clinerules],
+            strict: true,
+            ..GenerateArgs::default()
+        };
+        
+        let options = GenerateOptions::from_config(&config).with_args(&args);
+        
+        assert_eq!(options.format, OutputFormat::Json);
+        assert_eq!(options.targets, vec![OutputTarget::Clinerules]);
+        assert!(options.strict);
+        assert_eq!(options.rule_separator, "\n\n");
+    }
+    
+    #[test]
+    fn test_args_turn_config_settings_off() {
+        let config = ClinerConfig::parse("[output]\nmerge = true\n[sources]\nstrict = true\n", Path::new("cliner.toml")).unwrap();
+        let args = GenerateArgs { no_merge: true, no_strict: true, ..GenerateArgs::default() };
+        
+        let options = GenerateOptions::from_config(&config).with_args(&args);
+        
+        assert!(!options.merge);
+        assert!(!options.strict);
+        assert!(GenerateOptions::from_config(&config).with_args(&GenerateArgs::default()).merge);
+    }
+    
+    #[test]
+    fn test_with_profile() {
+        let config = ClinerConfig::parse(
+            "[profiles.ci]\nselect = \"!local-only\"\nremove_groups = [\"command\"]\n",
+            Path::new("cliner.toml"),
+        ).unwrap();
+        
+        let options = GenerateOptions::from_config(&config).with_profile(&config, "ci").unwrap();
+        
+        assert!(options.is_selected(&["backend".to_string()]));
+        assert!(!options.is_selected(&["local-only".to_string()]));
+        assert_eq!(options.remove_groups, vec!["command"]);
+        assert!(GenerateOptions::from_config(&config).with_profile(&config, "missing").is_err());
+    }
+    
+    #[test]
+    fn test_profile_budget_overrides_tokens() {
+        let config = ClinerConfig::parse(
+            "[tokens]\nbudget = 8000\n\n[profiles.small]\nbudget = 2000\non_exceed = \"trim\"\n\n[profiles.ci]\n",
+            Path::new("cliner.toml"),
+        ).unwrap();
+        
+        let small = GenerateOptions::from_config(&config).with_profile(&config, "small").unwrap();
+        let ci = GenerateOptions::from_config(&config).with_profile(&config, "ci").unwrap();
+        
+        assert_eq!((small.tokens.budget, small.tokens.on_exceed), (Some(2000), BudgetAction::Trim));
+        assert_eq!((ci.tokens.budget, ci.tokens.on_exceed), (Some(8000), BudgetAction::Warn));
+    }
+    
+    #[test]
+    fn test_with_selection_combines_profile_and_select() {
+        let config = ClinerConfig::parse("[profiles.ci]\nselect = \"!local-only\"\n", Path::new("cliner.toml")).unwrap();
+        let selection = SelectionArgs {
+            profile: Some("ci".to_string()),
+            select: Some(TagExpression::parse("rust || docs").unwrap()),
+        };
+        
+        let options = GenerateOptions::from_config(&config).with_selection(&config, &selection).unwrap();
+        
+        assert!(options.is_selected(&["rust".to_string()]));
+        assert!(!options.is_selected(&["rust".to_string(), "local-only".to_string()]));
+        assert_eq!(
+            options.selection_header().unwrap(),
+            "Generated by cliner with profile: ci, select: !local-only && (rust || docs)",
+        );
+        assert_eq!(GenerateOptions::default().selection_header(), None);
+    }
+    
+    #[test]
+    fn test_config_used_when_args_absent() {
+        let config = ClinerConfig::parse("[output]\nformat = \"yaml\"\nmerge = true\n", Path::new("cliner.toml")).unwrap();
+        
+        let options = GenerateOptions::from_config(&config).with_args(&GenerateArgs::default());
+        
+        assert_eq!(options.format, OutputFormat::Yaml);
+        assert!(options.merge);
+        assert!(options.is_target_enabled(OutputTarget::Roomodes));
+        assert!(options.is_target_enabled(OutputTarget::Clinerules));
+    }
+}
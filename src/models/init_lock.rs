@@ -0,0 +1,85 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use crate::error::Result;
+
+pub const INIT_LOCK_FILE_NAME: &str = "cliner-lock.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum InitSourceKind {
+    Global,
+    Template,
+    Directory,
+    Git,
+}
+
+/// Where `init` copied the project's modes and rules from.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct InitSource {
+    pub kind: InitSourceKind,
+    pub location: String,
+    #[serde(rename = "ref", skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct InitLock {
+    pub source: InitSource,
+    /// Files copied from the source, with the version last copied so `update` can merge upstream changes.
+    pub files: Vec<PathBuf>,
+}
+
+impl InitLock {
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        
+        let content = fs::read_to_string(path)?;
+        Ok(Some(serde_json::from_str(&content)?))
+    }
+    
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut content = serde_json::to_string_pretty(self)?;
+        content.push('\n');
+        fs::write(path, content)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+    
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let temp_dir = tempdir().unwrap();
+        let lock_path = temp_dir.path().join(INIT_LOCK_FILE_NAME);
+        let lock = InitLock {
+            source: InitSource {
+                kind: InitSourceKind::Git,
+                location: "file:///srv/rules.git".to_string(),
+                reference: Some("v1".to_string()),
+                commit: Some("0123abcd".to_string()),
+            },
+            files: vec![PathBuf::from("rules/00_style.md")],
+        };
+        
+        lock.save(&lock_path).unwrap();
+        let content = fs::read_to_string(&lock_path).unwrap();
+        assert!(content.contains("\"ref\": \"v1\""));
+        assert!(content.contains("\"kind\": \"git\""));
+        
+        assert_eq!(InitLock::load(&lock_path).unwrap(), Some(lock));
+    }
+    
+    #[test]
+    fn test_load_missing_file() {
+        let temp_dir = tempdir().unwrap();
+        assert_eq!(InitLock::load(&temp_dir.path().join(INIT_LOCK_FILE_NAME)).unwrap(), None);
+    }
+}
@@ -0,0 +1,13 @@
+use crate::models::{ConflictPolicy, SourceFilter};
+
+#[derive(Debug, Clone, Default)]
+pub struct InitOptions {
+    pub on_conflict: ConflictPolicy,
+    pub dry_run: bool,
+    pub link: bool,
+    pub template: Option<String>,
+    pub from: Option<String>,
+    pub reference: Option<String>,
+    pub global_layer: bool,
+    pub filter: SourceFilter,
+}
@@ -1,7 +1,43 @@
 mod mode;
 mod paths;
 mod cli;
+mod output_format;
+mod generate_options;
+mod output_target;
+mod sort_order;
+mod config;
+mod source;
+mod copy_outcome;
+mod init_options;
+mod init_lock;
+mod update_options;
+mod update_outcome;
+mod source_filter;
+mod source_metadata;
+mod tag_expression;
+mod undefined_policy;
+mod condition;
+mod token_estimator;
+mod budget_action;
 
 pub use mode::Mode;
-pub use paths::ClinePaths;
-pub use cli::{Cli, Commands};
+pub use paths::{ClinePaths, GlobalConfigCandidate, GlobalConfigEnv};
+pub use cli::{Cli, Commands, ConfigCommands, GenerateArgs, SelectionArgs};
+pub use output_format::OutputFormat;
+pub use generate_options::GenerateOptions;
+pub use output_target::{OutputTarget, parse_output_override};
+pub use sort_order::SortOrder;
+pub use config::{ClinerConfig, CommandConfig, TokensConfig, CONFIG_FILE_NAME};
+pub use source::{SourceDir, SourceFile, SourceKind, SourceLayer};
+pub use copy_outcome::{ConflictPolicy, CopyAction, CopyOutcome};
+pub use init_options::InitOptions;
+pub use init_lock::{InitLock, InitSource, InitSourceKind, INIT_LOCK_FILE_NAME};
+pub use update_options::UpdateOptions;
+pub use update_outcome::{UpdateAction, UpdateOutcome};
+pub use source_filter::{SkipReason, SkippedFile, SourceFilter, IGNORE_FILE_NAME, ORDER_FILE_NAME};
+pub use source_metadata::SourceMetadata;
+pub use tag_expression::{parse_tag_expression, TagExpression};
+pub use undefined_policy::UndefinedPolicy;
+pub use condition::Condition;
+pub use token_estimator::TokenEstimator;
+pub use budget_action::BudgetAction;
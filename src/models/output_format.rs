@@ -0,0 +1,27 @@
+use clap::ValueEnum;
+use serde::Deserialize;
+
+#[derive(ValueEnum, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    #[default]
+    Json,
+    Yaml,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_default_is_json() {
+        assert_eq!(OutputFormat::default(), OutputFormat::Json);
+    }
+    
+    #[test]
+    fn test_from_str() {
+        assert_eq!(OutputFormat::from_str("json", true).unwrap(), OutputFormat::Json);
+        assert_eq!(OutputFormat::from_str("yaml", true).unwrap(), OutputFormat::Yaml);
+        assert!(OutputFormat::from_str("toml", true).is_err());
+    }
+}
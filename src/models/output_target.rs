@@ -0,0 +1,81 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputTarget {
+    Roomodes,
+    Clinerules,
+}
+
+impl OutputTarget {
+    pub const ALL: [OutputTarget; 2] = [OutputTarget::Roomodes, OutputTarget::Clinerules];
+    
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutputTarget::Roomodes => "roomodes",
+            OutputTarget::Clinerules => "clinerules",
+        }
+    }
+    
+    pub fn default_file_name(&self) -> &'static str {
+        match self {
+            OutputTarget::Roomodes => ".roomodes",
+            OutputTarget::Clinerules => ".clinerules",
+        }
+    }
+}
+
+impl fmt::Display for OutputTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for OutputTarget {
+    type Err = String;
+    
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        OutputTarget::ALL
+            .into_iter()
+            .find(|target| target.as_str() == value)
+            .ok_or_else(|| format!("unknown output target '{}' (expected roomodes or clinerules)", value))
+    }
+}
+
+pub fn parse_output_override(value: &str) -> Result<(OutputTarget, PathBuf), String> {
+    let (target, path) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected TARGET=PATH, got '{}'", value))?;
+    
+    if path.is_empty() {
+        return Err(format!("missing path for output target '{}'", target));
+    }
+    
+    Ok((target.parse()?, PathBuf::from(path)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_from_str() {
+        assert_eq!("roomodes".parse::<OutputTarget>().unwrap(), OutputTarget::Roomodes);
+        assert_eq!("clinerules".parse::<OutputTarget>().unwrap(), OutputTarget::Clinerules);
+        assert!("cursor".parse::<OutputTarget>().is_err());
+    }
+    
+    #[test]
+    fn test_parse_output_override() {
+        let (target, path) = parse_output_override("roomodes=build/.roomodes").unwrap();
+        assert_eq!(target, OutputTarget::Roomodes);
+        assert_eq!(path, PathBuf::from("build/.roomodes"));
+        
+        assert!(parse_output_override("roomodes").is_err());
+        assert!(parse_output_override("roomodes=").is_err());
+        assert!(parse_output_override("unknown=path").is_err());
+    }
+}
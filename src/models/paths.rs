@@ -1,52 +1,282 @@
 use std::path::{Path, PathBuf};
 use std::fs;
 
-#[derive(Debug)]
+use crate::error::{ClinerError, Result};
+use crate::models::{ClinerConfig, ConflictPolicy, CopyAction, CopyOutcome, OutputTarget, INIT_LOCK_FILE_NAME, IGNORE_FILE_NAME, SourceDir, SourceFilter, SortOrder, SourceKind, SourceLayer, CONFIG_FILE_NAME};
+use crate::processors::FileProcessor;
+
+pub const LOCAL_DIR_NAME: &str = "local";
+pub const STATE_DIR_NAME: &str = ".cliner";
+
+#[derive(Debug, Clone, Default)]
+pub struct GlobalConfigEnv {
+    pub cliner_home: Option<PathBuf>,
+    pub home: Option<PathBuf>,
+    pub xdg_config_home: Option<PathBuf>,
+}
+
+impl GlobalConfigEnv {
+    pub fn from_env() -> Self {
+        Self {
+            cliner_home: Self::non_empty_var("CLINER_HOME"),
+            home: home::home_dir(),
+            // The XDG spec says relative values must be ignored
+            xdg_config_home: Self::non_empty_var("XDG_CONFIG_HOME").filter(|path| path.is_absolute()),
+        }
+    }
+    
+    fn non_empty_var(name: &str) -> Option<PathBuf> {
+        std::env::var_os(name)
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlobalConfigCandidate {
+    pub path: PathBuf,
+    /// Where the path came from, e.g. `$CLINER_HOME`
+    pub origin: &'static str,
+}
+
+#[derive(Debug, Clone)]
 pub struct ClinePaths {
+    pub root: PathBuf,
     pub base: PathBuf,
     pub modes: PathBuf,
     pub rules: PathBuf,
+    pub roomodes: PathBuf,
+    pub clinerules: PathBuf,
+    /// Global config directories, highest precedence first
+    pub global: Vec<PathBuf>,
+    pub organization: Option<PathBuf>,
+    pub local: Option<PathBuf>,
 }
 
 impl ClinePaths {
     pub fn new() -> Self {
-        let base = PathBuf::from(".cline");
+        Self::from_root(Path::new(""))
+    }
+    
+    pub fn from_root<P: AsRef<Path>>(root: P) -> Self {
+        let root = root.as_ref().to_path_buf();
+        let base = root.join(".cline");
         let modes = base.join("modes");
         let rules = base.join("rules");
+        let roomodes = root.join(OutputTarget::Roomodes.default_file_name());
+        let clinerules = root.join(OutputTarget::Clinerules.default_file_name());
+        let local = Some(base.join(LOCAL_DIR_NAME));
         
-        Self { base, modes, rules }
+        Self {
+            root,
+            base,
+            modes,
+            rules,
+            roomodes,
+            clinerules,
+            global: Vec::new(),
+            organization: None,
+            local,
+        }
     }
     
-    pub fn base_exists(&self) -> bool {
-        self.base.exists()
+    /// Walks up from `start` and returns the paths for the first directory containing `.cline`.
+    /// A `.cline` that is a global config directory, such as `$HOME/.cline`, is not a project.
+    pub fn discover<P: AsRef<Path>>(start: P) -> Option<Self> {
+        let global_dirs: Vec<PathBuf> = Self::global_config_candidates(&[], &GlobalConfigEnv::from_env())
+            .into_iter()
+            .map(|candidate| candidate.path)
+            .collect();
+        Self::discover_excluding(start, &global_dirs)
+    }
+    
+    fn discover_excluding<P: AsRef<Path>>(start: P, global_dirs: &[PathBuf]) -> Option<Self> {
+        let global_dirs: Vec<PathBuf> = global_dirs.iter().filter_map(|dir| dir.canonicalize().ok()).collect();
+        start
+            .as_ref()
+            .ancestors()
+            .find(|dir| {
+                let base = dir.join(".cline");
+                base.is_dir() && !base.canonicalize().is_ok_and(|base| global_dirs.contains(&base))
+            })
+            .map(Self::from_root)
+    }
+    
+    /// Uses `root` as-is when given, otherwise discovers `.cline` upwards from the current directory.
+    pub fn resolve(root: Option<&Path>) -> Result<Self> {
+        if let Some(root) = root {
+            return Ok(Self::from_root(root));
+        }
+        
+        let current_dir = std::env::current_dir()?;
+        Self::discover(&current_dir).ok_or_else(|| {
+            ClinerError::InvalidFormat(format!(
+                "no project .cline directory found in {} or its parents; run `cliner init` or pass --root",
+                current_dir.display()
+            ))
+        })
+    }
+    
+    pub fn config_file(&self) -> PathBuf {
+        self.base.join(CONFIG_FILE_NAME)
+    }
+    
+    pub fn init_lock_file(&self) -> PathBuf {
+        self.base.join(INIT_LOCK_FILE_NAME)
+    }
+    
+    pub fn ignore_file(&self) -> PathBuf {
+        self.base.join(IGNORE_FILE_NAME)
     }
     
-    pub fn modes_exists(&self) -> bool {
-        self.modes.exists()
+    /// Directory for cliner's own bookkeeping, such as snapshots of copied files.
+    pub fn state_dir(&self) -> PathBuf {
+        self.base.join(STATE_DIR_NAME)
     }
     
-    pub fn rules_exists(&self) -> bool {
-        self.rules.exists()
+    /// Where the last copied version of a tracked file (e.g. `rules/00_style.md`) is kept.
+    pub fn snapshot_file(&self, tracked_path: &Path) -> PathBuf {
+        self.state_dir().join("snapshots").join(tracked_path)
     }
     
-    pub fn get_global_config_paths() -> Vec<PathBuf> {
-        let mut paths = Vec::new();
+    /// Where the last output of an allowed `[commands]` entry is cached.
+    pub fn command_cache_dir(&self) -> PathBuf {
+        self.state_dir().join("commands")
+    }
+    
+    /// Files here replace sections of the generated project context rule.
+    pub fn context_dir(&self) -> PathBuf {
+        self.base.join("context")
+    }
+    
+    /// The project file for a tracked path such as `rules/00_style.md`.
+    pub fn tracked_file(&self, tracked_path: &Path) -> Option<PathBuf> {
+        let mut components = tracked_path.components();
+        let dir_name = components.next()?.as_os_str().to_str()?;
+        let kind = SourceKind::from_dir_name(dir_name)?;
+        Some(self.project_source_dir(kind).join(components.as_path()))
+    }
+    
+    pub fn apply_config(&mut self, config: &ClinerConfig) {
+        self.modes = self.base.join(&config.sources.modes);
+        self.rules = self.base.join(&config.sources.rules);
         
-        // $HOME/.cline
-        if let Some(home_dir) = home::home_dir() {
-            let home_cline = home_dir.join(".cline");
-            if home_cline.exists() && home_cline.is_dir() {
-                paths.push(home_cline);
-            }
-            
-            // $HOME/.config/cline
-            let config_cline = home_dir.join(".config").join("cline");
-            if config_cline.exists() && config_cline.is_dir() {
-                paths.push(config_cline);
-            }
+        if let Some(roomodes) = &config.output.roomodes {
+            self.roomodes = self.root.join(roomodes);
+        }
+        if let Some(clinerules) = &config.output.clinerules {
+            self.clinerules = self.root.join(clinerules);
+        }
+        
+        self.organization = config.layers.organization.as_ref().map(|dir| self.root.join(dir));
+        if !config.layers.local {
+            self.local = None;
+        }
+    }
+    
+    /// Enables the global layer using the directories found by `get_global_config_paths`.
+    pub fn use_global_config_paths(&mut self, global_dir_overrides: &[PathBuf]) {
+        self.global = Self::get_global_config_paths(global_dir_overrides);
+    }
+    
+    /// Source directories of the given kind in every layer, lowest precedence first.
+    pub fn source_dirs(&self, kind: SourceKind) -> Vec<SourceDir> {
+        let mut dirs = Vec::new();
+        
+        for global_dir in self.global.iter().rev() {
+            dirs.push(SourceDir { layer: SourceLayer::Global, path: global_dir.join(kind.dir_name()) });
+        }
+        if let Some(organization_dir) = &self.organization {
+            dirs.push(SourceDir { layer: SourceLayer::Organization, path: organization_dir.join(kind.dir_name()) });
+        }
+        
+        let project_dir = self.project_source_dir(kind).to_path_buf();
+        dirs.push(SourceDir { layer: SourceLayer::Project, path: project_dir });
+        
+        if let Some(local_dir) = &self.local {
+            dirs.push(SourceDir { layer: SourceLayer::Local, path: local_dir.join(kind.dir_name()) });
         }
         
-        paths
+        dirs
+    }
+    
+    /// Directories `@include` may read from: `.cline` and the global and organization layers.
+    pub fn include_roots(&self) -> Vec<PathBuf> {
+        let mut roots = vec![self.base.clone()];
+        roots.extend(self.global.iter().cloned());
+        roots.extend(self.organization.iter().cloned());
+        roots
+    }
+    
+    pub fn project_source_dir(&self, kind: SourceKind) -> &Path {
+        match kind {
+            SourceKind::Modes => &self.modes,
+            SourceKind::Rules => &self.rules,
+        }
+    }
+    
+    pub fn any_source_dir_exists(&self, kind: SourceKind) -> bool {
+        self.source_dirs(kind).iter().any(|dir| dir.path.is_dir())
+    }
+    
+    pub fn set_output_path(&mut self, target: OutputTarget, path: PathBuf) {
+        match target {
+            OutputTarget::Roomodes => self.roomodes = path,
+            OutputTarget::Clinerules => self.clinerules = path,
+        }
+    }
+    
+    pub fn base_exists(&self) -> bool {
+        self.base.exists()
+    }
+    
+    /// Existing global config directories, highest precedence first.
+    pub fn get_global_config_paths(global_dir_overrides: &[PathBuf]) -> Vec<PathBuf> {
+        Self::global_config_candidates(global_dir_overrides, &GlobalConfigEnv::from_env())
+            .into_iter()
+            .filter(|candidate| candidate.path.is_dir())
+            .map(|candidate| candidate.path)
+            .collect()
+    }
+    
+    /// Every directory searched for global config, highest precedence first.
+    ///
+    /// `--global-dir` replaces the search entirely. Otherwise `$CLINER_HOME` comes first,
+    /// followed by `$HOME/.cline` and `$XDG_CONFIG_HOME/cline` (`$HOME/.config/cline` when unset).
+    pub fn global_config_candidates(
+        global_dir_overrides: &[PathBuf],
+        env: &GlobalConfigEnv,
+    ) -> Vec<GlobalConfigCandidate> {
+        if !global_dir_overrides.is_empty() {
+            return global_dir_overrides
+                .iter()
+                .map(|path| GlobalConfigCandidate { path: path.clone(), origin: "--global-dir" })
+                .collect();
+        }
+        
+        let mut candidates = Vec::new();
+        
+        if let Some(cliner_home) = &env.cliner_home {
+            candidates.push(GlobalConfigCandidate { path: cliner_home.clone(), origin: "$CLINER_HOME" });
+        }
+        
+        if let Some(home_dir) = &env.home {
+            candidates.push(GlobalConfigCandidate { path: home_dir.join(".cline"), origin: "$HOME/.cline" });
+        }
+        
+        match (&env.xdg_config_home, &env.home) {
+            (Some(xdg_config_home), _) => candidates.push(GlobalConfigCandidate {
+                path: xdg_config_home.join("cline"),
+                origin: "$XDG_CONFIG_HOME/cline",
+            }),
+            (None, Some(home_dir)) => candidates.push(GlobalConfigCandidate {
+                path: home_dir.join(".config").join("cline"),
+                origin: "$HOME/.config/cline",
+            }),
+            (None, None) => {},
+        }
+        
+        candidates
     }
     
     pub fn create_directories(&self) -> std::io::Result<()> {
@@ -65,37 +295,187 @@ impl ClinePaths {
         Ok(())
     }
 
+    /// Copies the files in `src_dir` that `filter` accepts as `kind` sources into `dest_dir`,
+    /// resolving existing files with `policy`. With `dry_run` nothing is written and the
+    /// outcomes describe what would happen.
     pub fn copy_dir_contents<P: AsRef<Path>, Q: AsRef<Path>>(
         src_dir: P,
-        dest_dir: Q
-    ) -> std::io::Result<usize> {
+        dest_dir: Q,
+        kind: SourceKind,
+        filter: &SourceFilter,
+        policy: ConflictPolicy,
+        dry_run: bool,
+    ) -> Result<Vec<CopyOutcome>> {
+        let dest_dir = dest_dir.as_ref();
+        
         let src_dir = src_dir.as_ref();
+        
+        let mut outcomes = Vec::new();
+        for relative_path in Self::source_files(src_dir)? {
+            if !filter.is_copied(kind, &relative_path) {
+                continue;
+            }
+            let src_path = src_dir.join(&relative_path);
+            let dest_path = dest_dir.join(&relative_path);
+            let action = Self::copy_file(&src_path, &dest_path, policy, dry_run)?;
+            outcomes.push(CopyOutcome { source: src_path, destination: dest_path, action });
+        }
+        
+        Ok(outcomes)
+    }
+    
+    /// Like `copy_dir_contents`, but creates symlinks to the source files so the
+    /// destination always reflects their current content.
+    pub fn link_dir_contents<P: AsRef<Path>, Q: AsRef<Path>>(
+        src_dir: P,
+        dest_dir: Q,
+        kind: SourceKind,
+        filter: &SourceFilter,
+        policy: ConflictPolicy,
+        dry_run: bool,
+    ) -> Result<Vec<CopyOutcome>> {
         let dest_dir = dest_dir.as_ref();
         
-        if !src_dir.exists() || !src_dir.is_dir() {
-            return Ok(0);
+        let src_dir = src_dir.as_ref();
+        
+        let mut outcomes = Vec::new();
+        for relative_path in Self::source_files(src_dir)? {
+            if !filter.is_copied(kind, &relative_path) {
+                continue;
+            }
+            let src_path = src_dir.join(&relative_path);
+            let dest_path = dest_dir.join(&relative_path);
+            let target = fs::canonicalize(&src_path)?;
+            let action = Self::link_file(&target, &dest_path, policy, dry_run)?;
+            outcomes.push(CopyOutcome { source: src_path, destination: dest_path, action });
         }
         
-        if !dest_dir.exists() {
-            fs::create_dir_all(dest_dir)?;
+        Ok(outcomes)
+    }
+    
+    /// The files under `src_dir`, in the order `generate` reads them.
+    fn source_files(src_dir: &Path) -> Result<Vec<PathBuf>> {
+        if !src_dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        FileProcessor::collect_sorted_files(src_dir, SortOrder::default())
+    }
+    
+    fn link_file(target: &Path, dest_path: &Path, policy: ConflictPolicy, dry_run: bool) -> std::io::Result<CopyAction> {
+        if dest_path.is_symlink() && fs::read_link(dest_path)? == target {
+            return Ok(CopyAction::Unchanged);
         }
         
-        let mut copied_count = 0;
+        if !dest_path.exists() && !dest_path.is_symlink() {
+            if !dry_run {
+                Self::create_parent_dir(dest_path)?;
+                Self::create_symlink(target, dest_path)?;
+            }
+            return Ok(CopyAction::Linked);
+        }
         
-        for entry in fs::read_dir(src_dir)? {
-            let entry = entry?;
-            let src_path = entry.path();
-            
-            if src_path.is_file() {
-                if let Some(file_name) = src_path.file_name() {
-                    let dest_path = dest_dir.join(file_name);
-                    fs::copy(&src_path, &dest_path)?;
-                    copied_count += 1;
+        match policy {
+            ConflictPolicy::Skip => Ok(CopyAction::Skipped),
+            ConflictPolicy::Overwrite => {
+                if !dry_run {
+                    fs::remove_file(dest_path)?;
+                    Self::create_symlink(target, dest_path)?;
                 }
+                Ok(CopyAction::Overwritten)
+            },
+            ConflictPolicy::Backup => {
+                let backup_path = Self::available_backup_path(dest_path);
+                if !dry_run {
+                    fs::rename(dest_path, &backup_path)?;
+                    Self::create_symlink(target, dest_path)?;
+                }
+                Ok(CopyAction::BackedUp(backup_path))
+            },
+        }
+    }
+    
+    #[cfg(unix)]
+    fn create_symlink(target: &Path, link: &Path) -> std::io::Result<()> {
+        std::os::unix::fs::symlink(target, link)
+    }
+    
+    #[cfg(windows)]
+    fn create_symlink(target: &Path, link: &Path) -> std::io::Result<()> {
+        std::os::windows::fs::symlink_file(target, link)
+    }
+    
+    fn copy_file(src_path: &Path, dest_path: &Path, policy: ConflictPolicy, dry_run: bool) -> std::io::Result<CopyAction> {
+        let content = fs::read(src_path)?;
+        Self::write_file_contents(&content, dest_path, policy, dry_run)
+    }
+    
+    /// Writes `content` to `dest_path`, resolving an existing file with `policy`. A symlink
+    /// at `dest_path`, e.g. from `init --link`, is replaced rather than written through.
+    pub fn write_file_contents(content: &[u8], dest_path: &Path, policy: ConflictPolicy, dry_run: bool) -> std::io::Result<CopyAction> {
+        let is_symlink = dest_path.is_symlink();
+        if !dest_path.exists() && !is_symlink {
+            if !dry_run {
+                Self::create_parent_dir(dest_path)?;
+                fs::write(dest_path, content)?;
             }
+            return Ok(CopyAction::Copied);
         }
         
-        Ok(copied_count)
+        if fs::read(dest_path).ok().as_deref() == Some(content) {
+            return Ok(CopyAction::Unchanged);
+        }
+        
+        match policy {
+            ConflictPolicy::Skip => Ok(CopyAction::Skipped),
+            ConflictPolicy::Overwrite => {
+                if !dry_run {
+                    if is_symlink {
+                        fs::remove_file(dest_path)?;
+                    }
+                    fs::write(dest_path, content)?;
+                }
+                Ok(CopyAction::Overwritten)
+            },
+            ConflictPolicy::Backup => {
+                let backup_path = Self::available_backup_path(dest_path);
+                if !dry_run {
+                    fs::rename(dest_path, &backup_path)?;
+                    fs::write(dest_path, content)?;
+                }
+                Ok(CopyAction::BackedUp(backup_path))
+            },
+        }
+    }
+    
+    fn create_parent_dir(path: &Path) -> std::io::Result<()> {
+        match path.parent() {
+            Some(parent_dir) if !parent_dir.as_os_str().is_empty() => fs::create_dir_all(parent_dir),
+            _ => Ok(()),
+        }
+    }
+    
+    fn available_backup_path(path: &Path) -> PathBuf {
+        let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+        file_name.push(".bak");
+        let backup_path = path.with_file_name(&file_name);
+        if !backup_path.exists() {
+            return backup_path;
+        }
+        
+        (1..)
+            .map(|index| {
+                let mut numbered_name = file_name.clone();
+                numbered_name.push(format!(".{}", index));
+                path.with_file_name(numbered_name)
+            })
+            .find(|candidate| !candidate.exists())
+            .unwrap_or(backup_path)
+    }
+}
+
+impl Default for ClinePaths {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -112,6 +492,168 @@ mod tests {
         assert_eq!(paths.rules, PathBuf::from(".cline").join("rules"));
     }
     
+    #[test]
+    fn test_from_root() {
+        let paths = ClinePaths::from_root("/project");
+        
+        assert_eq!(paths.root, PathBuf::from("/project"));
+        assert_eq!(paths.base, PathBuf::from("/project/.cline"));
+        assert_eq!(paths.modes, PathBuf::from("/project/.cline/modes"));
+        assert_eq!(paths.roomodes, PathBuf::from("/project/.roomodes"));
+        assert_eq!(paths.clinerules, PathBuf::from("/project/.clinerules"));
+    }
+    
+    #[test]
+    fn test_discover_walks_up_to_cline_directory() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(temp_dir.path().join(".cline")).unwrap();
+        let nested = temp_dir.path().join("src").join("deep");
+        fs::create_dir_all(&nested).unwrap();
+        
+        let paths = ClinePaths::discover(&nested).unwrap();
+        
+        assert_eq!(paths.root, temp_dir.path());
+        assert_eq!(paths.base, temp_dir.path().join(".cline"));
+    }
+    
+    #[test]
+    fn test_discover_without_cline_directory() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        
+        let nested = temp_dir.path().join("src");
+        fs::create_dir_all(&nested).unwrap();
+        
+        assert!(ClinePaths::discover_excluding(&nested, &[]).is_none());
+    }
+    
+    #[test]
+    fn test_discover_skips_global_config_directory() {
+        let home_dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(home_dir.path().join(".cline")).unwrap();
+        let nested = home_dir.path().join("work").join("project");
+        fs::create_dir_all(&nested).unwrap();
+        
+        assert!(ClinePaths::discover_excluding(&nested, &[home_dir.path().join(".cline")]).is_none());
+        assert_eq!(ClinePaths::discover_excluding(&nested, &[]).unwrap().root, home_dir.path());
+    }
+    
+    #[test]
+    fn test_apply_config() {
+        let config = ClinerConfig::parse(
+            "[sources]\nrules = \"agent-rules\"\n[output]\nroomodes = \"build/.roomodes\"\n",
+            Path::new(CONFIG_FILE_NAME),
+        ).unwrap();
+        
+        let mut paths = ClinePaths::from_root("/project");
+        paths.apply_config(&config);
+        
+        assert_eq!(paths.config_file(), PathBuf::from("/project/.cline/cliner.toml"));
+        assert_eq!(paths.modes, PathBuf::from("/project/.cline/modes"));
+        assert_eq!(paths.rules, PathBuf::from("/project/.cline/agent-rules"));
+        assert_eq!(paths.roomodes, PathBuf::from("/project/build/.roomodes"));
+        assert_eq!(paths.clinerules, PathBuf::from("/project/.clinerules"));
+    }
+    
+    #[test]
+    fn test_source_dirs_in_layer_order() {
+        let mut paths = ClinePaths::from_root("/project");
+        paths.global = vec![PathBuf::from("/home/user/.cline")];
+        paths.organization = Some(PathBuf::from("/org"));
+        
+        let dirs = paths.source_dirs(SourceKind::Rules);
+        
+        assert_eq!(dirs, vec![
+            SourceDir { layer: SourceLayer::Global, path: PathBuf::from("/home/user/.cline/rules") },
+            SourceDir { layer: SourceLayer::Organization, path: PathBuf::from("/org/rules") },
+            SourceDir { layer: SourceLayer::Project, path: PathBuf::from("/project/.cline/rules") },
+            SourceDir { layer: SourceLayer::Local, path: PathBuf::from("/project/.cline/local/rules") },
+        ]);
+    }
+    
+    #[test]
+    fn test_global_config_candidates_default_order() {
+        let env = GlobalConfigEnv {
+            cliner_home: None,
+            home: Some(PathBuf::from("/home/user")),
+            xdg_config_home: None,
+        };
+        
+        let candidates = ClinePaths::global_config_candidates(&[], &env);
+        let paths: Vec<&Path> = candidates.iter().map(|candidate| candidate.path.as_path()).collect();
+        
+        assert_eq!(paths, vec![Path::new("/home/user/.cline"), Path::new("/home/user/.config/cline")]);
+    }
+    
+    #[test]
+    fn test_global_config_candidates_with_cliner_home_and_xdg() {
+        let env = GlobalConfigEnv {
+            cliner_home: Some(PathBuf::from("/team/cline")),
+            home: Some(PathBuf::from("/home/user")),
+            xdg_config_home: Some(PathBuf::from("/xdg")),
+        };
+        
+        let candidates = ClinePaths::global_config_candidates(&[], &env);
+        
+        assert_eq!(candidates, vec![
+            GlobalConfigCandidate { path: PathBuf::from("/team/cline"), origin: "$CLINER_HOME" },
+            GlobalConfigCandidate { path: PathBuf::from("/home/user/.cline"), origin: "$HOME/.cline" },
+            GlobalConfigCandidate { path: PathBuf::from("/xdg/cline"), origin: "$XDG_CONFIG_HOME/cline" },
+        ]);
+    }
+    
+    #[test]
+    fn test_global_dir_overrides_replace_search() {
+        let env = GlobalConfigEnv {
+            cliner_home: Some(PathBuf::from("/team/cline")),
+            home: Some(PathBuf::from("/home/user")),
+            xdg_config_home: None,
+        };
+        
+        let candidates = ClinePaths::global_config_candidates(&[PathBuf::from("/shared")], &env);
+        
+        assert_eq!(candidates, vec![
+            GlobalConfigCandidate { path: PathBuf::from("/shared"), origin: "--global-dir" },
+        ]);
+    }
+    
+    #[test]
+    fn test_apply_config_layers() {
+        let config = ClinerConfig::parse(
+            "[layers]\norganization = \"../org\"\nlocal = false\n",
+            Path::new(CONFIG_FILE_NAME),
+        ).unwrap();
+        
+        let mut paths = ClinePaths::from_root("/project");
+        paths.apply_config(&config);
+        
+        assert_eq!(paths.organization, Some(PathBuf::from("/project/../org")));
+        assert_eq!(paths.local, None);
+    }
+    
+    #[test]
+    fn test_tracked_and_snapshot_files() {
+        let paths = ClinePaths::from_root("/project");
+        
+        assert_eq!(
+            paths.tracked_file(Path::new("rules/00_style.md")),
+            Some(PathBuf::from("/project/.cline/rules/00_style.md"))
+        );
+        assert_eq!(paths.tracked_file(Path::new("other/00_style.md")), None);
+        assert_eq!(
+            paths.snapshot_file(Path::new("rules/00_style.md")),
+            PathBuf::from("/project/.cline/.cliner/snapshots/rules/00_style.md")
+        );
+    }
+    
+    #[test]
+    fn test_set_output_path() {
+        let mut paths = ClinePaths::new();
+        paths.set_output_path(OutputTarget::Roomodes, PathBuf::from("build/.roomodes"));
+        
+        assert_eq!(paths.roomodes, PathBuf::from("build/.roomodes"));
+        assert_eq!(paths.clinerules, PathBuf::from(".clinerules"));
+    }
+    
     #[test]
     fn test_path_components() {
         let paths = ClinePaths::new();
@@ -122,4 +664,150 @@ mod tests {
         let expected_rules_path = paths.base.join("rules");
         assert_eq!(paths.rules, expected_rules_path);
     }
+    
+    fn copy_fixture() -> (tempfile::TempDir, PathBuf, PathBuf) {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let src_dir = temp_dir.path().join("global");
+        let dest_dir = temp_dir.path().join("project");
+        fs::create_dir_all(&src_dir).unwrap();
+        fs::create_dir_all(&dest_dir).unwrap();
+        
+        fs::write(src_dir.join("new.md"), "new").unwrap();
+        fs::write(src_dir.join("same.md"), "same").unwrap();
+        fs::write(src_dir.join("changed.md"), "global version").unwrap();
+        fs::write(dest_dir.join("same.md"), "same").unwrap();
+        fs::write(dest_dir.join("changed.md"), "local edits").unwrap();
+        
+        (temp_dir, src_dir, dest_dir)
+    }
+    
+    fn actions(outcomes: &[CopyOutcome]) -> Vec<(String, CopyAction)> {
+        outcomes
+            .iter()
+            .map(|outcome| (outcome.destination.file_name().unwrap().to_string_lossy().into_owned(), outcome.action.clone()))
+            .collect()
+    }
+    
+    #[test]
+    fn test_copy_dir_contents_skip_keeps_existing_files() {
+        let (_temp_dir, src_dir, dest_dir) = copy_fixture();
+        
+        let outcomes = ClinePaths::copy_dir_contents(&src_dir, &dest_dir, SourceKind::Rules, &SourceFilter::default(), ConflictPolicy::Skip, false).unwrap();
+        
+        assert_eq!(actions(&outcomes), vec![
+            ("changed.md".to_string(), CopyAction::Skipped),
+            ("new.md".to_string(), CopyAction::Copied),
+            ("same.md".to_string(), CopyAction::Unchanged),
+        ]);
+        assert_eq!(fs::read_to_string(dest_dir.join("changed.md")).unwrap(), "local edits");
+        assert_eq!(fs::read_to_string(dest_dir.join("new.md")).unwrap(), "new");
+    }
+    
+    #[test]
+    fn test_copy_dir_contents_overwrite() {
+        let (_temp_dir, src_dir, dest_dir) = copy_fixture();
+        
+        let outcomes = ClinePaths::copy_dir_contents(&src_dir, &dest_dir, SourceKind::Rules, &SourceFilter::default(), ConflictPolicy::Overwrite, false).unwrap();
+        
+        assert_eq!(outcomes[0].action, CopyAction::Overwritten);
+        assert_eq!(fs::read_to_string(dest_dir.join("changed.md")).unwrap(), "global version");
+    }
+    
+    #[test]
+    fn test_copy_dir_contents_backup() {
+        let (_temp_dir, src_dir, dest_dir) = copy_fixture();
+        fs::write(dest_dir.join("changed.md.bak"), "older backup").unwrap();
+        
+        let outcomes = ClinePaths::copy_dir_contents(&src_dir, &dest_dir, SourceKind::Rules, &SourceFilter::default(), ConflictPolicy::Backup, false).unwrap();
+        
+        assert_eq!(outcomes[0].action, CopyAction::BackedUp(dest_dir.join("changed.md.bak.1")));
+        assert_eq!(fs::read_to_string(dest_dir.join("changed.md")).unwrap(), "global version");
+        assert_eq!(fs::read_to_string(dest_dir.join("changed.md.bak.1")).unwrap(), "local edits");
+        assert_eq!(fs::read_to_string(dest_dir.join("changed.md.bak")).unwrap(), "older backup");
+    }
+    
+    #[test]
+    fn test_copy_dir_contents_dry_run_changes_nothing() {
+        let (temp_dir, src_dir, dest_dir) = copy_fixture();
+        let missing_dest = temp_dir.path().join("missing");
+        
+        let outcomes = ClinePaths::copy_dir_contents(&src_dir, &dest_dir, SourceKind::Rules, &SourceFilter::default(), ConflictPolicy::Overwrite, true).unwrap();
+        assert_eq!(outcomes[0].action, CopyAction::Overwritten);
+        assert_eq!(fs::read_to_string(dest_dir.join("changed.md")).unwrap(), "local edits");
+        assert!(!dest_dir.join("new.md").exists());
+        
+        let outcomes = ClinePaths::copy_dir_contents(&src_dir, &missing_dest, SourceKind::Rules, &SourceFilter::default(), ConflictPolicy::Skip, true).unwrap();
+        assert_eq!(outcomes.len(), 3);
+        assert!(!missing_dest.exists());
+    }
+    
+    #[cfg(unix)]
+    #[test]
+    fn test_link_dir_contents() {
+        let (_temp_dir, src_dir, dest_dir) = copy_fixture();
+        
+        let outcomes = ClinePaths::link_dir_contents(&src_dir, &dest_dir, SourceKind::Rules, &SourceFilter::default(), ConflictPolicy::Overwrite, false).unwrap();
+        
+        assert_eq!(actions(&outcomes), vec![
+            ("changed.md".to_string(), CopyAction::Overwritten),
+            ("new.md".to_string(), CopyAction::Linked),
+            ("same.md".to_string(), CopyAction::Overwritten),
+        ]);
+        assert!(dest_dir.join("new.md").is_symlink());
+        fs::write(src_dir.join("changed.md"), "edited globally").unwrap();
+        assert_eq!(fs::read_to_string(dest_dir.join("changed.md")).unwrap(), "edited globally");
+        
+        let outcomes = ClinePaths::link_dir_contents(&src_dir, &dest_dir, SourceKind::Rules, &SourceFilter::default(), ConflictPolicy::Skip, false).unwrap();
+        assert!(outcomes.iter().all(|outcome| outcome.action == CopyAction::Unchanged));
+    }
+    
+    #[test]
+    fn test_copy_dir_contents_applies_filter() {
+        let (_temp_dir, src_dir, dest_dir) = copy_fixture();
+        fs::write(src_dir.join("new.md.bak"), "old").unwrap();
+        fs::write(src_dir.join("order.txt"), "new.md\n").unwrap();
+        fs::create_dir_all(src_dir.join("drafts")).unwrap();
+        fs::write(src_dir.join("drafts").join("idea.md"), "idea").unwrap();
+        let ignore_file = src_dir.join(IGNORE_FILE_NAME);
+        fs::write(&ignore_file, "rules/drafts/\n").unwrap();
+        let filter = SourceFilter::new(&["*.md".to_string()], &[], Some(&ignore_file)).unwrap();
+        
+        let outcomes = ClinePaths::copy_dir_contents(&src_dir, &dest_dir, SourceKind::Rules, &filter, ConflictPolicy::Skip, false).unwrap();
+        
+        let copied: Vec<String> = actions(&outcomes).into_iter().map(|(name, _)| name).collect();
+        assert_eq!(copied, vec!["changed.md", "new.md", "order.txt", "same.md"]);
+        assert!(!dest_dir.join("drafts").exists());
+    }
+    
+    #[cfg(unix)]
+    #[test]
+    fn test_write_file_contents_replaces_links() {
+        let (_temp_dir, src_dir, dest_dir) = copy_fixture();
+        ClinePaths::link_dir_contents(&src_dir, &dest_dir, SourceKind::Rules, &SourceFilter::default(), ConflictPolicy::Overwrite, false).unwrap();
+        
+        let action = ClinePaths::write_file_contents(b"template", &dest_dir.join("new.md"), ConflictPolicy::Overwrite, false).unwrap();
+        assert_eq!(action, CopyAction::Overwritten);
+        assert!(!dest_dir.join("new.md").is_symlink());
+        assert_eq!(fs::read_to_string(dest_dir.join("new.md")).unwrap(), "template");
+        
+        let action = ClinePaths::write_file_contents(b"template", &dest_dir.join("same.md"), ConflictPolicy::Backup, false).unwrap();
+        assert_eq!(action, CopyAction::BackedUp(dest_dir.join("same.md.bak")));
+        assert!(!dest_dir.join("same.md").is_symlink());
+        assert!(dest_dir.join("same.md.bak").is_symlink());
+        
+        assert_eq!(fs::read_to_string(src_dir.join("new.md")).unwrap(), "new");
+        assert_eq!(fs::read_to_string(src_dir.join("same.md")).unwrap(), "same");
+    }
+    
+    #[test]
+    fn test_copy_dir_contents_nested() {
+        let (_temp_dir, src_dir, dest_dir) = copy_fixture();
+        fs::create_dir_all(src_dir.join("backend")).unwrap();
+        fs::write(src_dir.join("backend").join("api.md"), "api").unwrap();
+        
+        let outcomes = ClinePaths::copy_dir_contents(&src_dir, &dest_dir, SourceKind::Rules, &SourceFilter::default(), ConflictPolicy::Skip, false).unwrap();
+        
+        assert_eq!(outcomes[0].destination, dest_dir.join("backend").join("api.md"));
+        assert_eq!(fs::read_to_string(dest_dir.join("backend").join("api.md")).unwrap(), "api");
+    }
 }
@@ -0,0 +1,133 @@
+use std::cmp::Ordering;
+use std::ffi::OsStr;
+use std::path::Path;
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum SortOrder {
+    /// Compare runs of digits by value, so `9_a.md` sorts before `10_b.md`
+    #[default]
+    Natural,
+    Lexical,
+    CaseInsensitive,
+}
+
+impl SortOrder {
+    pub fn compare(&self, a: &OsStr, b: &OsStr) -> Ordering {
+        match self {
+            SortOrder::Natural => Self::compare_natural(&a.to_string_lossy(), &b.to_string_lossy()).then_with(|| a.cmp(b)),
+            SortOrder::Lexical => a.cmp(b),
+            SortOrder::CaseInsensitive => {
+                let a_lower = a.to_string_lossy().to_lowercase();
+                let b_lower = b.to_string_lossy().to_lowercase();
+                a_lower.cmp(&b_lower).then_with(|| a.cmp(b))
+            },
+        }
+    }
+    
+    fn compare_natural(a: &str, b: &str) -> Ordering {
+        let mut a_chunks = NaturalChunks { rest: a };
+        let mut b_chunks = NaturalChunks { rest: b };
+        
+        loop {
+            let ordering = match (a_chunks.next(), b_chunks.next()) {
+                (Some(a_chunk), Some(b_chunk)) => {
+                    let a_is_number = a_chunk.starts_with(|c: char| c.is_ascii_digit());
+                    let b_is_number = b_chunk.starts_with(|c: char| c.is_ascii_digit());
+                    if a_is_number && b_is_number {
+                        let a_digits = a_chunk.trim_start_matches('0');
+                        let b_digits = b_chunk.trim_start_matches('0');
+                        a_digits.len().cmp(&b_digits.len()).then_with(|| a_digits.cmp(b_digits))
+                    } else {
+                        a_chunk.cmp(b_chunk)
+                    }
+                },
+                (None, None) => return Ordering::Equal,
+                (None, Some(_)) => return Ordering::Less,
+                (Some(_), None) => return Ordering::Greater,
+            };
+            
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+    }
+    
+    /// Compares relative paths one component at a time, so `backend/b.md` sorts with
+    /// the other files in `backend/` rather than by its full string.
+    pub fn compare_paths(&self, a: &Path, b: &Path) -> Ordering {
+        let mut a_components = a.components();
+        let mut b_components = b.components();
+        
+        loop {
+            match (a_components.next(), b_components.next()) {
+                (Some(a_component), Some(b_component)) => {
+                    let ordering = self.compare(a_component.as_os_str(), b_component.as_os_str());
+                    if ordering != Ordering::Equal {
+                        return ordering;
+                    }
+                },
+                (None, None) => return Ordering::Equal,
+                (None, Some(_)) => return Ordering::Less,
+                (Some(_), None) => return Ordering::Greater,
+            }
+        }
+    }
+}
+
+/// Splits a name into alternating runs of digits and non-digits.
+struct NaturalChunks<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Iterator for NaturalChunks<'a> {
+    type Item = &'a str;
+    
+    fn next(&mut self) -> Option<&'a str> {
+        let first = self.rest.chars().next()?;
+        let is_digit = first.is_ascii_digit();
+        let end = self.rest.find(|c: char| c.is_ascii_digit() != is_digit).unwrap_or(self.rest.len());
+        let (chunk, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        Some(chunk)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn sorted(order: SortOrder, names: &[&str]) -> Vec<String> {
+        let mut names: Vec<&OsStr> = names.iter().map(OsStr::new).collect();
+        names.sort_by(|a, b| order.compare(a, b));
+        names.iter().map(|name| name.to_string_lossy().into_owned()).collect()
+    }
+    
+    #[test]
+    fn test_lexical() {
+        assert_eq!(sorted(SortOrder::Lexical, &["b.md", "README.md", "a.md"]), vec!["README.md", "a.md", "b.md"]);
+    }
+    
+    #[test]
+    fn test_case_insensitive() {
+        assert_eq!(sorted(SortOrder::CaseInsensitive, &["b.md", "README.md", "a.md"]), vec!["a.md", "b.md", "README.md"]);
+    }
+    
+    #[test]
+    fn test_natural() {
+        assert_eq!(
+            sorted(SortOrder::Natural, &["10_x.md", "9_y.md", "09_z.md", "a2.md", "a10.md"]),
+            vec!["9_y.md", "09_z.md", "10_x.md", "a2.md", "a10.md"]
+        );
+        assert_eq!(sorted(SortOrder::Lexical, &["10_x.md", "9_y.md"]), vec!["10_x.md", "9_y.md"]);
+    }
+    
+    #[test]
+    fn test_compare_paths() {
+        let mut paths = vec![Path::new("frontend/a.md"), Path::new("backend/z.md"), Path::new("00.md"), Path::new("backend/a.md")];
+        paths.sort_by(|a, b| SortOrder::Lexical.compare_paths(a, b));
+        
+        assert_eq!(paths, vec![Path::new("00.md"), Path::new("backend/a.md"), Path::new("backend/z.md"), Path::new("frontend/a.md")]);
+    }
+}
@@ -0,0 +1,141 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use crate::models::SourceMetadata;
+
+/// Where a source file comes from. Later layers override earlier ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SourceLayer {
+    Global,
+    Organization,
+    Project,
+    Local,
+}
+
+impl SourceLayer {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SourceLayer::Global => "global",
+            SourceLayer::Organization => "organization",
+            SourceLayer::Project => "project",
+            SourceLayer::Local => "local",
+        }
+    }
+}
+
+impl fmt::Display for SourceLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceKind {
+    Modes,
+    Rules,
+}
+
+impl SourceKind {
+    pub fn dir_name(&self) -> &'static str {
+        match self {
+            SourceKind::Modes => "modes",
+            SourceKind::Rules => "rules",
+        }
+    }
+    
+    pub fn from_dir_name(dir_name: &str) -> Option<Self> {
+        [SourceKind::Modes, SourceKind::Rules]
+            .into_iter()
+            .find(|kind| kind.dir_name() == dir_name)
+    }
+    
+    pub fn item_name(&self) -> &'static str {
+        match self {
+            SourceKind::Modes => "mode",
+            SourceKind::Rules => "rule",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceDir {
+    pub layer: SourceLayer,
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceFile {
+    /// Path relative to the layer's source directory, e.g. `backend/00_api.md`
+    pub name: PathBuf,
+    pub path: PathBuf,
+    pub layer: SourceLayer,
+    /// Layers that also had a file with this name and were overridden
+    pub overrides: Vec<SourceLayer>,
+    /// Filled in by `FileProcessor::read_metadata`
+    pub metadata: SourceMetadata,
+}
+
+impl SourceFile {
+    pub fn new(name: PathBuf, path: PathBuf, layer: SourceLayer) -> Self {
+        Self { name, path, layer, overrides: Vec::new(), metadata: SourceMetadata::default() }
+    }
+    
+    /// Folder tags followed by the tags from the file's metadata, without duplicates.
+    pub fn tags(&self) -> Vec<String> {
+        let mut tags = self.directory_tags();
+        for tag in &self.metadata.tags {
+            if !tags.contains(tag) {
+                tags.push(tag.clone());
+            }
+        }
+        tags
+    }
+    
+    /// The folders a file is nested in, outermost first; `backend/api/00.md` is tagged
+    /// `backend` and `api`.
+    pub fn directory_tags(&self) -> Vec<String> {
+        self.name
+            .parent()
+            .map(Path::components)
+            .into_iter()
+            .flatten()
+            .map(|component| component.as_os_str().to_string_lossy().into_owned())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_layer_precedence() {
+        assert!(SourceLayer::Global < SourceLayer::Organization);
+        assert!(SourceLayer::Organization < SourceLayer::Project);
+        assert!(SourceLayer::Project < SourceLayer::Local);
+    }
+    
+    #[test]
+    fn test_display() {
+        assert_eq!(SourceLayer::Organization.to_string(), "organization");
+        assert_eq!(SourceKind::Rules.dir_name(), "rules");
+        assert_eq!(SourceKind::from_dir_name("modes"), Some(SourceKind::Modes));
+        assert_eq!(SourceKind::from_dir_name("local"), None);
+    }
+    
+    #[test]
+    fn test_directory_tags() {
+        let nested = SourceFile::new(PathBuf::from("backend/api/00.md"), PathBuf::from("/p/backend/api/00.md"), SourceLayer::Project);
+        let top_level = SourceFile::new(PathBuf::from("00.md"), PathBuf::from("/p/00.md"), SourceLayer::Project);
+        
+        assert_eq!(nested.directory_tags(), vec!["backend", "api"]);
+        assert!(top_level.directory_tags().is_empty());
+    }
+    
+    #[test]
+    fn test_tags_include_metadata() {
+        let mut source = SourceFile::new(PathBuf::from("backend/00.md"), PathBuf::from("/p/backend/00.md"), SourceLayer::Project);
+        source.metadata.tags = vec!["security".to_string(), "backend".to_string()];
+        
+        assert_eq!(source.tags(), vec!["backend", "security"]);
+    }
+}
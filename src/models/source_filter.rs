@@ -0,0 +1,162 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use crate::error::{ClinerError, Result};
+use crate::models::SourceKind;
+
+pub const IGNORE_FILE_NAME: &str = ".clineignore";
+pub const DEFAULT_INCLUDE: &str = "*.md";
+/// Lists files in the order they should be used, one relative path per line
+pub const ORDER_FILE_NAME: &str = "order.txt";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    NotIncluded,
+    Excluded,
+    Ignored,
+    OrderFile,
+    Disabled,
+    NotSelected,
+}
+
+impl SkipReason {
+    pub fn description(&self) -> &'static str {
+        match self {
+            SkipReason::NotIncluded => "not matched by sources.include",
+            SkipReason::Excluded => "matched by sources.exclude",
+            SkipReason::Ignored => "ignored by .clineignore",
+            SkipReason::OrderFile => "order index",
+            SkipReason::Disabled => "enabled: false",
+            SkipReason::NotSelected => "tags not selected",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedFile {
+    pub path: PathBuf,
+    pub reason: SkipReason,
+}
+
+impl fmt::Display for SkippedFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.path.display(), self.reason.description())
+    }
+}
+
+/// Decides which files in the mode and rule directories are sources.
+#[derive(Debug, Clone)]
+pub struct SourceFilter {
+    include: GlobSet,
+    exclude: GlobSet,
+    ignore: Gitignore,
+}
+
+impl SourceFilter {
+    pub fn new(include: &[String], exclude: &[String], ignore_file: Option<&Path>) -> Result<Self> {
+        let ignore = match ignore_file {
+            Some(ignore_file) if ignore_file.is_file() => {
+                let mut builder = GitignoreBuilder::new("");
+                if let Some(err) = builder.add(ignore_file) {
+                    return Err(ClinerError::Config(format!("{}: {}", ignore_file.display(), err)));
+                }
+                builder
+                    .build()
+                    .map_err(|err| ClinerError::Config(format!("{}: {}", ignore_file.display(), err)))?
+            },
+            _ => Gitignore::empty(),
+        };
+        
+        Ok(Self {
+            include: Self::build_glob_set(include).map_err(|err| ClinerError::Config(err.to_string()))?,
+            exclude: Self::build_glob_set(exclude).map_err(|err| ClinerError::Config(err.to_string()))?,
+            ignore,
+        })
+    }
+    
+    pub fn build_glob_set(patterns: &[String]) -> std::result::Result<GlobSet, globset::Error> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns {
+            builder.add(Glob::new(pattern)?);
+        }
+        
+        builder.build()
+    }
+    
+    /// Whether `init` and `update` copy the file at `relative_path`: every source, and the order index.
+    pub fn is_copied(&self, kind: SourceKind, relative_path: &Path) -> bool {
+        matches!(self.check(kind, relative_path), None | Some(SkipReason::OrderFile))
+    }
+    
+    /// Returns why a file at `relative_path` inside the `kind` directory is skipped, if it is.
+    pub fn check(&self, kind: SourceKind, relative_path: &Path) -> Option<SkipReason> {
+        if relative_path == Path::new(ORDER_FILE_NAME) {
+            return Some(SkipReason::OrderFile);
+        }
+        
+        let ignore_path = Path::new(kind.dir_name()).join(relative_path);
+        if self.ignore.matched_path_or_any_parents(&ignore_path, false).is_ignore() {
+            return Some(SkipReason::Ignored);
+        }
+        
+        if self.exclude.is_match(relative_path) {
+            return Some(SkipReason::Excluded);
+        }
+        
+        if !self.include.is_match(relative_path) {
+            return Some(SkipReason::NotIncluded);
+        }
+        
+        None
+    }
+}
+
+impl Default for SourceFilter {
+    fn default() -> Self {
+        Self::new(&[DEFAULT_INCLUDE.to_string()], &[], None).expect("default include pattern is valid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+    
+    #[test]
+    fn test_default_only_includes_markdown() {
+        let filter = SourceFilter::default();
+        
+        assert_eq!(filter.check(SourceKind::Rules, Path::new("00_style.md")), None);
+        assert_eq!(filter.check(SourceKind::Rules, Path::new("backend/00_api.md")), None);
+        assert_eq!(filter.check(SourceKind::Rules, Path::new("00_style.md.bak")), Some(SkipReason::NotIncluded));
+        assert_eq!(filter.check(SourceKind::Rules, Path::new(".DS_Store")), Some(SkipReason::NotIncluded));
+        assert_eq!(filter.check(SourceKind::Rules, Path::new(ORDER_FILE_NAME)), Some(SkipReason::OrderFile));
+        assert!(filter.is_copied(SourceKind::Rules, Path::new(ORDER_FILE_NAME)));
+        assert!(!filter.is_copied(SourceKind::Rules, Path::new(".DS_Store")));
+    }
+    
+    #[test]
+    fn test_include_exclude_and_ignore_file() {
+        let temp_dir = tempdir().unwrap();
+        let ignore_file = temp_dir.path().join(IGNORE_FILE_NAME);
+        fs::write(&ignore_file, "# drafts are never published\nrules/drafts/\n!modes/keep.txt\n").unwrap();
+        
+        let filter = SourceFilter::new(
+            &["*.md".to_string(), "*.txt".to_string()],
+            &["**/wip-*".to_string()],
+            Some(&ignore_file),
+        ).unwrap();
+        
+        assert_eq!(filter.check(SourceKind::Rules, Path::new("notes.txt")), None);
+        assert_eq!(filter.check(SourceKind::Rules, Path::new("backend/wip-api.md")), Some(SkipReason::Excluded));
+        assert_eq!(filter.check(SourceKind::Rules, Path::new("drafts/00.md")), Some(SkipReason::Ignored));
+        assert_eq!(filter.check(SourceKind::Modes, Path::new("drafts/00.md")), None);
+    }
+    
+    #[test]
+    fn test_invalid_glob() {
+        assert!(SourceFilter::new(&["[".to_string()], &[], None).is_err());
+    }
+}
@@ -0,0 +1,48 @@
+use serde::Deserialize;
+
+/// Optional frontmatter keys of a rule, or header keys of a mode.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct SourceMetadata {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    /// `enabled: false` leaves the file out of every output
+    pub enabled: bool,
+    /// Higher is more important
+    pub priority: Option<i64>,
+    /// Overrides file-name order; lower comes first
+    pub order: Option<i64>,
+}
+
+impl Default for SourceMetadata {
+    fn default() -> Self {
+        Self {
+            title: None,
+            description: None,
+            tags: Vec::new(),
+            enabled: true,
+            priority: None,
+            order: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_deserialize() {
+        let metadata: SourceMetadata = serde_yaml::from_str(
+            "title: Security\ntags: [backend, security]\nenabled: false\npriority: 10\nauthor: someone\n",
+        ).unwrap();
+        
+        assert_eq!(metadata.title.as_deref(), Some("Security"));
+        assert_eq!(metadata.tags, vec!["backend", "security"]);
+        assert!(!metadata.enabled);
+        assert_eq!(metadata.priority, Some(10));
+        assert_eq!(metadata.order, None);
+        assert!(SourceMetadata::default().enabled);
+    }
+}
@@ -0,0 +1,323 @@
+use std::fmt;
+use crate::error::{ClinerError, Result};
+
+/// A boolean expression over tags, e.g. `rust && !legacy` or `(backend || api) && !draft`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TagExpression {
+    Tag(String),
+    Not(Box<TagExpression>),
+    And(Box<TagExpression>, Box<TagExpression>),
+    Or(Box<TagExpression>, Box<TagExpression>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Token {
+    Name(String),
+    Text(String),
+    Equals,
+    NotEquals,
+    Not,
+    And,
+    Or,
+    Open,
+    Close,
+}
+
+/// Shared by tag expressions and `@if` conditions so both parse the same way.
+pub(crate) trait BooleanExpression: Sized {
+    const KIND: &'static str;
+    const NAME: &'static str;
+    
+    fn name(parser: &mut Parser<'_>, name: String) -> std::result::Result<Self, String>;
+    fn not(inner: Self) -> Self;
+    fn and(left: Self, right: Self) -> Self;
+    fn or(left: Self, right: Self) -> Self;
+}
+
+impl TagExpression {
+    pub fn parse(input: &str) -> Result<Self> {
+        Self::parse_expression(input).map_err(ClinerError::InvalidFormat)
+    }
+    
+    fn parse_expression(input: &str) -> std::result::Result<Self, String> {
+        Parser::parse(input)
+    }
+    
+    pub fn matches(&self, tags: &[String]) -> bool {
+        match self {
+            TagExpression::Tag(tag) => tags.iter().any(|candidate| candidate == tag),
+            TagExpression::Not(inner) => !inner.matches(tags),
+            TagExpression::And(left, right) => left.matches(tags) && right.matches(tags),
+            TagExpression::Or(left, right) => left.matches(tags) || right.matches(tags),
+        }
+    }
+    
+    /// Combines two optional selections so a file must match both.
+    pub fn both(first: Option<TagExpression>, second: Option<TagExpression>) -> Option<TagExpression> {
+        match (first, second) {
+            (Some(first), Some(second)) => Some(TagExpression::And(Box::new(first), Box::new(second))),
+            (first, second) => first.or(second),
+        }
+    }
+    
+    fn precedence(&self) -> u8 {
+        match self {
+            TagExpression::Or(_, _) => 0,
+            TagExpression::And(_, _) => 1,
+            TagExpression::Not(_) | TagExpression::Tag(_) => 2,
+        }
+    }
+    
+    fn fmt_operand(&self, f: &mut fmt::Formatter<'_>, parent_precedence: u8) -> fmt::Result {
+        if self.precedence() < parent_precedence {
+            write!(f, "({})", self)
+        } else {
+            write!(f, "{}", self)
+        }
+    }
+}
+
+impl BooleanExpression for TagExpression {
+    const KIND: &'static str = "tag expression";
+    const NAME: &'static str = "tag";
+    
+    fn name(_parser: &mut Parser<'_>, name: String) -> std::result::Result<Self, String> {
+        Ok(TagExpression::Tag(name))
+    }
+    
+    fn not(inner: Self) -> Self {
+        TagExpression::Not(Box::new(inner))
+    }
+    
+    fn and(left: Self, right: Self) -> Self {
+        TagExpression::And(Box::new(left), Box::new(right))
+    }
+    
+    fn or(left: Self, right: Self) -> Self {
+        TagExpression::Or(Box::new(left), Box::new(right))
+    }
+}
+
+/// Parses a `--select` value for clap.
+pub fn parse_tag_expression(value: &str) -> std::result::Result<TagExpression, String> {
+    TagExpression::parse_expression(value)
+}
+
+impl fmt::Display for TagExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TagExpression::Tag(tag) => write!(f, "{}", tag),
+            TagExpression::Not(inner) => {
+                write!(f, "!")?;
+                inner.fmt_operand(f, 2)
+            },
+            TagExpression::And(left, right) => {
+                left.fmt_operand(f, 1)?;
+                write!(f, " && ")?;
+                right.fmt_operand(f, 2)
+            },
+            TagExpression::Or(left, right) => {
+                left.fmt_operand(f, 0)?;
+                write!(f, " || ")?;
+                right.fmt_operand(f, 1)
+            },
+        }
+    }
+}
+
+pub(crate) struct Parser<'a> {
+    kind: &'static str,
+    noun: &'static str,
+    input: &'a str,
+    tokens: &'a [(usize, Token)],
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    pub(crate) fn parse<E: BooleanExpression>(input: &str) -> std::result::Result<E, String> {
+        let tokens = Self::tokenize(E::KIND, input)?;
+        let mut parser = Parser { kind: E::KIND, noun: E::NAME, input, tokens: &tokens, position: 0 };
+        
+        let expression = parser.parse_or::<E>()?;
+        if let Some((column, token)) = parser.peek() {
+            return Err(parser.error(*column, &format!("unexpected {}", parser.describe(token))));
+        }
+        
+        Ok(expression)
+    }
+    
+    fn tokenize(kind: &str, input: &str) -> std::result::Result<Vec<(usize, Token)>, String> {
+        let mut tokens = Vec::new();
+        let mut chars = input.char_indices().peekable();
+        
+        while let Some((index, c)) = chars.next() {
+            let column = input[..index].chars().count() + 1;
+            let token = match c {
+                c if c.is_whitespace() => continue,
+                '!' if chars.next_if(|(_, next)| *next == '=').is_some() => Token::NotEquals,
+                '!' => Token::Not,
+                '(' => Token::Open,
+                ')' => Token::Close,
+                '=' | '&' | '|' => {
+                    if chars.next_if(|(_, next)| *next == c).is_none() {
+                        return Err(Self::syntax_error(kind, input, column, &format!("expected '{}{}'", c, c)));
+                    }
+                    match c {
+                        '=' => Token::Equals,
+                        '&' => Token::And,
+                        _ => Token::Or,
+                    }
+                },
+                '"' => {
+                    let mut text = String::new();
+                    loop {
+                        match chars.next() {
+                            Some((_, '"')) => break,
+                            Some((_, next)) => text.push(next),
+                            None => return Err(Self::syntax_error(kind, input, column, "unclosed string")),
+                        }
+                    }
+                    Token::Text(text)
+                },
+                c if Self::is_name_char(c) => {
+                    let mut name = c.to_string();
+                    while let Some((_, next)) = chars.next_if(|(_, next)| Self::is_name_char(*next)) {
+                        name.push(next);
+                    }
+                    Token::Name(name)
+                },
+                c => return Err(Self::syntax_error(kind, input, column, &format!("unexpected character '{}'", c))),
+            };
+            tokens.push((column, token));
+        }
+        
+        Ok(tokens)
+    }
+    
+    fn is_name_char(c: char) -> bool {
+        c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | '/')
+    }
+    
+    fn syntax_error(kind: &str, input: &str, column: usize, message: &str) -> String {
+        format!("invalid {} '{}': {} at column {}", kind, input, message, column)
+    }
+    
+    fn describe(&self, token: &Token) -> String {
+        match token {
+            Token::Name(name) => format!("{} '{}'", self.noun, name),
+            Token::Text(text) => format!("string \"{}\"", text),
+            Token::Equals => "'=='".to_string(),
+            Token::NotEquals => "'!='".to_string(),
+            Token::Not => "'!'".to_string(),
+            Token::And => "'&&'".to_string(),
+            Token::Or => "'||'".to_string(),
+            Token::Open => "'('".to_string(),
+            Token::Close => "')'".to_string(),
+        }
+    }
+    
+    fn peek(&self) -> Option<&'a (usize, Token)> {
+        self.tokens.get(self.position)
+    }
+    
+    pub(crate) fn next(&mut self) -> Option<&'a (usize, Token)> {
+        let token = self.peek();
+        self.position += 1;
+        token
+    }
+    
+    pub(crate) fn next_if(&mut self, expected: &Token) -> bool {
+        match self.peek() {
+            Some((_, token)) if token == expected => {
+                self.position += 1;
+                true
+            },
+            _ => false,
+        }
+    }
+    
+    /// The column of the next token, or just past the end of the input.
+    pub(crate) fn column(&self) -> usize {
+        self.peek().map(|(column, _)| *column).unwrap_or(self.input.chars().count() + 1)
+    }
+    
+    pub(crate) fn error(&self, column: usize, message: &str) -> String {
+        Self::syntax_error(self.kind, self.input, column, message)
+    }
+    
+    fn parse_or<E: BooleanExpression>(&mut self) -> std::result::Result<E, String> {
+        let mut expression = self.parse_and()?;
+        while self.next_if(&Token::Or) {
+            expression = E::or(expression, self.parse_and()?);
+        }
+        Ok(expression)
+    }
+    
+    fn parse_and<E: BooleanExpression>(&mut self) -> std::result::Result<E, String> {
+        let mut expression = self.parse_unary()?;
+        while self.next_if(&Token::And) {
+            expression = E::and(expression, self.parse_unary()?);
+        }
+        Ok(expression)
+    }
+    
+    fn parse_unary<E: BooleanExpression>(&mut self) -> std::result::Result<E, String> {
+        let column = self.column();
+        let Some((_, token)) = self.next() else {
+            return Err(self.error(column, &format!("expected a {}", self.noun)));
+        };
+        
+        match token {
+            Token::Name(name) => E::name(self, name.clone()),
+            Token::Not => Ok(E::not(self.parse_unary()?)),
+            Token::Open => {
+                let expression = self.parse_or()?;
+                if !self.next_if(&Token::Close) {
+                    return Err(self.error(self.column(), "expected ')'"));
+                }
+                Ok(expression)
+            },
+            other => Err(self.error(column, &format!("expected a {}, found {}", self.noun, self.describe(other)))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn tags(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+    
+    #[test]
+    fn test_matches() {
+        let expression = TagExpression::parse("rust && !legacy || docs").unwrap();
+        
+        assert!(expression.matches(&tags(&["rust"])));
+        assert!(!expression.matches(&tags(&["rust", "legacy"])));
+        assert!(expression.matches(&tags(&["legacy", "docs"])));
+        assert!(!expression.matches(&[]));
+    }
+    
+    #[test]
+    fn test_display_round_trips() {
+        for input in ["rust && !legacy", "(backend || api) && !draft", "!(a && b) || c", "a || b && c"] {
+            let expression = TagExpression::parse(input).unwrap();
+            assert_eq!(expression.to_string(), input);
+            assert_eq!(TagExpression::parse(&expression.to_string()).unwrap(), expression);
+        }
+    }
+    
+    #[test]
+    fn test_errors_name_the_column() {
+        let message = |input: &str| TagExpression::parse(input).unwrap_err().to_string();
+        
+        assert!(message("rust &").contains("expected '&&' at column 6"));
+        assert!(message("rust && ").contains("expected a tag at column 9"));
+        assert!(message("(rust").contains("expected ')' at column 6"));
+        assert!(message("rust legacy").contains("unexpected tag 'legacy' at column 6"));
+        assert!(message("rust && $x").contains("unexpected character '$' at column 9"));
+        assert!(message("&& rust").contains("expected a tag, found '&&' at column 1"));
+    }
+}
@@ -0,0 +1,12 @@
+use serde::Deserialize;
+
+/// How `stats` and the token budget approximate a tokenizer, without calling one.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum TokenEstimator {
+    /// Characters divided by `chars_per_token`
+    #[default]
+    Chars,
+    /// Whitespace-separated words multiplied by `tokens_per_word`
+    Words,
+}
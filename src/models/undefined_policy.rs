@@ -0,0 +1,13 @@
+use serde::Deserialize;
+
+/// What to do with a `{{variable}}` that has no value.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum UndefinedPolicy {
+    #[default]
+    Error,
+    /// Replace it with an empty string
+    Empty,
+    /// Leave the `{{variable}}` text as it is
+    Keep,
+}
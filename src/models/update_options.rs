@@ -0,0 +1,8 @@
+use crate::models::SourceFilter;
+
+#[derive(Debug, Clone, Default)]
+pub struct UpdateOptions {
+    pub dry_run: bool,
+    pub reference: Option<String>,
+    pub filter: SourceFilter,
+}
@@ -0,0 +1,86 @@
+use std::fmt;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateAction {
+    /// Local file already matches upstream
+    UpToDate,
+    /// Only the local file changed; upstream did not
+    KeptLocal,
+    /// Local file was unmodified and now holds the upstream version
+    Updated,
+    /// New upstream file
+    Added,
+    /// Removed upstream and unmodified locally
+    Removed,
+    /// Local and upstream changes combined cleanly
+    Merged,
+    /// Local and upstream changes overlap; the file holds conflict markers
+    Conflict(usize),
+    /// Removed upstream but modified locally; left in place and no longer tracked
+    Orphaned,
+    /// Deleted locally; upstream changes were not applied
+    DeletedLocally,
+    /// Exists locally but was not copied at init, so there is no base to merge against
+    Untracked,
+}
+
+impl UpdateAction {
+    pub fn label(&self) -> &'static str {
+        match self {
+            UpdateAction::UpToDate => "current",
+            UpdateAction::KeptLocal => "local",
+            UpdateAction::Updated => "update",
+            UpdateAction::Added => "add",
+            UpdateAction::Removed => "remove",
+            UpdateAction::Merged => "merge",
+            UpdateAction::Conflict(_) => "conflict",
+            UpdateAction::Orphaned => "orphaned",
+            UpdateAction::DeletedLocally => "deleted",
+            UpdateAction::Untracked => "skip",
+        }
+    }
+    
+    pub fn changes_destination(&self) -> bool {
+        matches!(
+            self,
+            UpdateAction::Updated | UpdateAction::Added | UpdateAction::Removed | UpdateAction::Merged | UpdateAction::Conflict(_)
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpdateOutcome {
+    pub destination: PathBuf,
+    pub action: UpdateAction,
+}
+
+impl fmt::Display for UpdateOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:<10} {}", self.action.label(), self.destination.display())?;
+        
+        match self.action {
+            UpdateAction::KeptLocal => write!(f, " (unchanged upstream, keeping local edits)"),
+            UpdateAction::Conflict(count) => write!(f, " ({} conflicting {})", count, if count == 1 { "hunk" } else { "hunks" }),
+            UpdateAction::Orphaned => write!(f, " (removed upstream, keeping local edits)"),
+            UpdateAction::DeletedLocally => write!(f, " (deleted locally, upstream changes not applied)"),
+            UpdateAction::Untracked => write!(f, " (exists with different content and was not copied at init)"),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_display() {
+        let outcome = UpdateOutcome {
+            destination: PathBuf::from(".cline/rules/a.md"),
+            action: UpdateAction::Conflict(2),
+        };
+        
+        assert_eq!(outcome.to_string(), "conflict   .cline/rules/a.md (2 conflicting hunks)");
+    }
+}
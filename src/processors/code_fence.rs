@@ -0,0 +1,47 @@
+/// Tracks whether the lines of a Markdown file are inside a fenced code block.
+#[derive(Debug, Default)]
+pub struct CodeFence {
+    open: Option<(char, usize)>,
+}
+
+impl CodeFence {
+    /// Takes the next line and returns whether it belongs to a code block, fence lines included.
+    pub fn update(&mut self, line: &str) -> bool {
+        let trimmed = line.trim_start();
+        let fence = trimmed
+            .chars()
+            .next()
+            .filter(|c| matches!(c, '`' | '~'))
+            .map(|c| (c, trimmed.chars().take_while(|next| *next == c).count()))
+            .filter(|(_, length)| *length >= 3);
+        
+        match (self.open, fence) {
+            (None, Some(fence)) => {
+                self.open = Some(fence);
+                true
+            },
+            (Some((open_char, open_length)), Some((c, length)))
+                if c == open_char && length >= open_length && trimmed[length..].trim().is_empty() =>
+            {
+                self.open = None;
+                true
+            },
+            (open, _) => open.is_some(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_update() {
+        let mut fence = CodeFence::default();
+        let lines = ["text", "````markdown", "```yaml", "{{ x }}", "```", "````", "after", "~~~", "~~~"];
+        
+        let inside: Vec<bool> = lines.iter().map(|line| fence.update(line)).collect();
+        
+        assert_eq!(inside, vec![false, true, true, true, true, true, false, true, true]);
+    }
+}
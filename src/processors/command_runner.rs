@@ -0,0 +1,225 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::models::CommandConfig;
+
+/// How much of a failing command's stderr goes into the error message.
+const STDERR_LIMIT: usize = 4096;
+
+/// Runs the commands allowed by `[commands]` for `{{command:NAME}}`, caching their output so `check`
+/// can use it without running anything.
+#[derive(Debug, Default)]
+pub struct CommandRunner {
+    commands: BTreeMap<String, CommandConfig>,
+    root: PathBuf,
+    cache_dir: PathBuf,
+    cached_only: bool,
+    outputs: Mutex<BTreeMap<String, String>>,
+}
+
+impl CommandRunner {
+    pub fn new(commands: BTreeMap<String, CommandConfig>, root: PathBuf, cache_dir: PathBuf, cached_only: bool) -> Self {
+        Self { commands, root, cache_dir, cached_only, outputs: Mutex::new(BTreeMap::new()) }
+    }
+    
+    pub fn output(&self, name: &str) -> Result<String, String> {
+        let Some(command) = self.commands.get(name) else {
+            return Err(format!("command '{}' is not allowed; add it to [commands] in cliner.toml", name));
+        };
+        
+        let mut outputs = self.outputs.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(output) = outputs.get(name) {
+            return Ok(output.clone());
+        }
+        
+        let cache_file = self.cache_dir.join(format!("{}.txt", name));
+        let output = if self.cached_only {
+            fs::read_to_string(&cache_file)
+                .map_err(|_| format!("no cached output for command '{}'; run `cliner generate` first", name))?
+        } else {
+            let output = Self::run(name, command, &self.root)?;
+            fs::create_dir_all(&self.cache_dir)
+                .and_then(|_| fs::write(&cache_file, &output))
+                .map_err(|e| format!("cannot cache output of command '{}': {}", name, e))?;
+            output
+        };
+        
+        outputs.insert(name.to_string(), output.clone());
+        Ok(output)
+    }
+    
+    fn run(name: &str, command: &CommandConfig, root: &Path) -> Result<String, String> {
+        let (program, args) = command.run.split_first().ok_or_else(|| format!("command '{}' has nothing to run", name))?;
+        let mut process = Command::new(program);
+        process
+            .args(args)
+            .current_dir(root)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        // A group of its own lets a timeout kill whatever the command started, too.
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut process, 0);
+        let mut child = process.spawn().map_err(|e| format!("cannot run command '{}': {}", name, e))?;
+        
+        // Read one byte past the limit so an oversized output can be told apart from one that fits.
+        // Stderr is read to the end so a chatty command never blocks on it, but only the start is kept.
+        let limit = command.max_output_bytes;
+        let mut stdout = Some(Self::read_in_background(child.stdout.take(), limit + 1, false));
+        let stderr = Self::read_in_background(child.stderr.take(), STDERR_LIMIT, true);
+        let deadline = Instant::now() + Duration::from_secs(command.timeout_secs);
+        
+        // Wait for the command to exit and for stdout to close; a process it started in the
+        // background may keep stdout open, and that counts against the timeout as well.
+        let mut status = None;
+        let mut output = None;
+        let status = loop {
+            if status.is_none() {
+                status = child.try_wait().map_err(|e| format!("cannot wait for command '{}': {}", name, e))?;
+            }
+            if let Some(reader) = stdout.take_if(|reader| reader.is_finished()) {
+                output = Some(reader.join().unwrap_or_default());
+            }
+            
+            if output.as_ref().is_some_and(|output| output.len() > limit) {
+                Self::kill(&mut child);
+                return Err(format!("output of command '{}' exceeds {} bytes", name, limit));
+            }
+            if let (Some(status), Some(_)) = (status, &output) {
+                break status;
+            }
+            if Instant::now() >= deadline {
+                Self::kill(&mut child);
+                return Err(format!("command '{}' timed out after {}s", name, command.timeout_secs));
+            }
+            thread::sleep(Duration::from_millis(10));
+        };
+        
+        let output = output.unwrap_or_default();
+        if !status.success() {
+            let stderr = Self::join_before(stderr, deadline);
+            let message = String::from_utf8_lossy(&stderr);
+            return Err(format!("command '{}' failed with {}: {}", name, status, message.trim()));
+        }
+        
+        let output = String::from_utf8(output).map_err(|_| format!("output of command '{}' is not UTF-8", name))?;
+        Ok(output.trim_end().to_string())
+    }
+    
+    /// Kills the command and everything else in its process group.
+    fn kill(child: &mut Child) {
+        #[cfg(unix)]
+        if let Ok(group) = i32::try_from(child.id()) {
+            // SAFETY: `kill` has no memory safety requirements; a negative pid names the group.
+            unsafe {
+                libc::kill(-group, libc::SIGKILL);
+            }
+        }
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+    
+    /// Reads up to `limit` bytes; with `drain`, keeps reading to the end and drops the rest.
+    fn read_in_background<R: Read + Send + 'static>(pipe: Option<R>, limit: usize, drain: bool) -> thread::JoinHandle<Vec<u8>> {
+        thread::spawn(move || {
+            let mut buffer = Vec::new();
+            let Some(mut pipe) = pipe else {
+                return buffer;
+            };
+            let _ = pipe.by_ref().take(limit as u64).read_to_end(&mut buffer);
+            if drain {
+                let _ = io::copy(&mut pipe, &mut io::sink());
+            }
+            buffer
+        })
+    }
+    
+    fn join_before(reader: thread::JoinHandle<Vec<u8>>, deadline: Instant) -> Vec<u8> {
+        while !reader.is_finished() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        if reader.is_finished() { reader.join().unwrap_or_default() } else { Vec::new() }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+    
+    fn command(script: &str) -> CommandConfig {
+        CommandConfig {
+            run: vec!["sh".to_string(), "-c".to_string(), script.to_string()],
+            timeout_secs: 2,
+            max_output_bytes: 16,
+        }
+    }
+    
+    fn runner(dir: &Path, script: &str, cached_only: bool) -> CommandRunner {
+        let commands = BTreeMap::from([("members".to_string(), command(script))]);
+        CommandRunner::new(commands, dir.to_path_buf(), dir.join("cache"), cached_only)
+    }
+    
+    #[test]
+    fn test_output_is_cached() {
+        let dir = tempdir().unwrap();
+        
+        assert_eq!(runner(dir.path(), "echo core; echo cli", false).output("members").unwrap(), "core\ncli");
+        assert_eq!(fs::read_to_string(dir.path().join("cache/members.txt")).unwrap(), "core\ncli");
+        assert_eq!(runner(dir.path(), "echo changed", true).output("members").unwrap(), "core\ncli");
+    }
+    
+    #[test]
+    fn test_only_allowed_commands_run() {
+        let dir = tempdir().unwrap();
+        
+        let message = runner(dir.path(), "echo hi", false).output("rm").unwrap_err();
+        
+        assert!(message.contains("command 'rm' is not allowed"));
+    }
+    
+    #[test]
+    fn test_missing_cache() {
+        let dir = tempdir().unwrap();
+        
+        let message = runner(dir.path(), "echo hi", true).output("members").unwrap_err();
+        
+        assert!(message.contains("no cached output for command 'members'"));
+    }
+    
+    #[test]
+    fn test_limits_and_failures() {
+        let dir = tempdir().unwrap();
+        let output = |script: &str| runner(dir.path(), script, false).output("members").unwrap_err();
+        
+        assert!(output("sleep 5").contains("timed out after 2s"));
+        assert!(output("yes").contains("exceeds 16 bytes"));
+        assert!(output("echo 12345678901234567").contains("exceeds 16 bytes"));
+        assert!(output("echo broken >&2; exit 3").contains("broken"));
+    }
+    
+    #[test]
+    fn test_verbose_stderr_does_not_block() {
+        let dir = tempdir().unwrap();
+        
+        let output = runner(dir.path(), "head -c 200000 /dev/zero >&2; echo done", false).output("members");
+        
+        assert_eq!(output.unwrap(), "done");
+    }
+    
+    #[test]
+    fn test_background_process_holding_stdout_times_out() {
+        let dir = tempdir().unwrap();
+        let started = Instant::now();
+        
+        let message = runner(dir.path(), "sleep 30 & echo started", false).output("members").unwrap_err();
+        
+        assert!(message.contains("timed out after 2s"), "{}", message);
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+}
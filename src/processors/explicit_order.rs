@@ -0,0 +1,189 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::error::{ClinerError, Result};
+use crate::models::{SourceDir, SourceFile, ORDER_FILE_NAME};
+
+/// Paths listed in an `order.txt` index, relative to the source directory.
+struct OrderIndex {
+    path: PathBuf,
+    entries: Vec<PathBuf>,
+}
+
+impl OrderIndex {
+    fn position(&self, name: &Path) -> Option<i64> {
+        self.entries.iter().position(|entry| entry == name).map(|index| index as i64 + 1)
+    }
+}
+
+/// Applies `order:` keys and `order.txt` on top of file-name order.
+pub struct ExplicitOrder;
+
+impl ExplicitOrder {
+    /// `order.txt` entries are checked against `collected`, which still holds disabled and unselected files.
+    pub fn apply(sources: Vec<SourceFile>, collected: &[SourceFile], source_dirs: &[SourceDir], strict: bool) -> Result<Vec<SourceFile>> {
+        let index = Self::load_index(source_dirs)?;
+        
+        let mut keyed_sources: Vec<(Option<i64>, SourceFile)> = sources
+            .into_iter()
+            .map(|source| {
+                let order = source.metadata.order.or_else(|| index.as_ref().and_then(|index| index.position(&source.name)));
+                (order, source)
+            })
+            .collect();
+        
+        if let Some(index) = &index {
+            Self::report_missing_entries(index, collected, strict)?;
+        }
+        Self::report_ties(&keyed_sources, strict)?;
+        
+        // The sort is stable, so ties and unordered files keep their file-name order.
+        keyed_sources.sort_by_key(|(order, _)| (order.is_none(), *order));
+        Ok(keyed_sources.into_iter().map(|(_, source)| source).collect())
+    }
+    
+    /// Reads `order.txt` from the highest-precedence layer that has one.
+    fn load_index(source_dirs: &[SourceDir]) -> Result<Option<OrderIndex>> {
+        for source_dir in source_dirs.iter().rev() {
+            let path = source_dir.path.join(ORDER_FILE_NAME);
+            if !path.is_file() {
+                continue;
+            }
+            
+            let entries = fs::read_to_string(&path)?
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(PathBuf::from)
+                .collect();
+            return Ok(Some(OrderIndex { path, entries }));
+        }
+        
+        Ok(None)
+    }
+    
+    fn report_missing_entries(index: &OrderIndex, collected: &[SourceFile], strict: bool) -> Result<()> {
+        for entry in &index.entries {
+            if collected.iter().any(|source| &source.name == entry) {
+                continue;
+            }
+            
+            let message = format!("{} lists '{}', which is not a source file", index.path.display(), entry.display());
+            if strict {
+                return Err(ClinerError::InvalidFormat(message));
+            }
+            eprintln!("Warning: {}", message);
+        }
+        
+        Ok(())
+    }
+    
+    fn report_ties(keyed_sources: &[(Option<i64>, SourceFile)], strict: bool) -> Result<()> {
+        let mut by_order: BTreeMap<i64, Vec<&Path>> = BTreeMap::new();
+        for (order, source) in keyed_sources {
+            if let Some(order) = order {
+                by_order.entry(*order).or_default().push(&source.name);
+            }
+        }
+        
+        for (order, names) in by_order.into_iter().filter(|(_, names)| names.len() > 1) {
+            let names: Vec<String> = names.iter().map(|name| name.display().to_string()).collect();
+            let message = format!("Ambiguous order {} shared by {}", order, names.join(", "));
+            if strict {
+                return Err(ClinerError::InvalidFormat(message));
+            }
+            eprintln!("Warning: {}; using file name order", message);
+        }
+        
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::{tempdir, TempDir};
+    use crate::models::{SourceKind, SourceLayer};
+    use crate::processors::FileProcessor;
+    
+    fn sources(dir: &TempDir, kind: SourceKind, files: &[(&str, &str)]) -> (Vec<SourceFile>, Vec<SourceDir>) {
+        let sources = files
+            .iter()
+            .map(|(name, content)| {
+                let path = dir.path().join(name);
+                fs::write(&path, content).unwrap();
+                SourceFile::new(PathBuf::from(name), path, SourceLayer::Project)
+            })
+            .collect();
+        let sources = FileProcessor::read_metadata(sources, kind, true).unwrap();
+        (sources, vec![SourceDir { layer: SourceLayer::Project, path: dir.path().to_path_buf() }])
+    }
+    
+    fn names(sources: &[SourceFile]) -> Vec<&str> {
+        sources.iter().map(|source| source.name.to_str().unwrap()).collect()
+    }
+    
+    #[test]
+    fn test_rule_order_key() {
+        let dir = tempdir().unwrap();
+        let (files, dirs) = sources(&dir, SourceKind::Rules, &[
+            ("a.md", "no order"),
+            ("b.md", "---\norder: 2\n---\nsecond"),
+            ("c.md", "---\norder: 1\n---\nfirst"),
+        ]);
+        
+        let ordered = ExplicitOrder::apply(files.clone(), &files, &dirs, true).unwrap();
+        
+        assert_eq!(names(&ordered), vec!["c.md", "b.md", "a.md"]);
+    }
+    
+    #[test]
+    fn test_mode_order_key() {
+        let dir = tempdir().unwrap();
+        let (files, dirs) = sources(&dir, SourceKind::Modes, &[
+            ("a.md", "name: A\norder: 5\n---\nA"),
+            ("b.md", "name: B\norder: 3\n---\nB"),
+        ]);
+        
+        let ordered = ExplicitOrder::apply(files.clone(), &files, &dirs, true).unwrap();
+        
+        assert_eq!(names(&ordered), vec!["b.md", "a.md"]);
+    }
+    
+    #[test]
+    fn test_order_index() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join(ORDER_FILE_NAME), "# most important first\nc.md\na.md\n").unwrap();
+        let (files, dirs) = sources(&dir, SourceKind::Rules, &[("a.md", "a"), ("b.md", "b"), ("c.md", "c")]);
+        
+        let ordered = ExplicitOrder::apply(files.clone(), &files, &dirs, true).unwrap();
+        
+        assert_eq!(names(&ordered), vec!["c.md", "a.md", "b.md"]);
+    }
+    
+    #[test]
+    fn test_order_index_may_list_files_left_out() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join(ORDER_FILE_NAME), "b.md\na.md\n").unwrap();
+        let (files, dirs) = sources(&dir, SourceKind::Rules, &[("a.md", "a"), ("b.md", "---\nenabled: false\n---\nb")]);
+        let enabled: Vec<SourceFile> = files.iter().filter(|source| source.metadata.enabled).cloned().collect();
+        
+        let ordered = ExplicitOrder::apply(enabled, &files, &dirs, true).unwrap();
+        
+        assert_eq!(names(&ordered), vec!["a.md"]);
+    }
+    
+    #[test]
+    fn test_ties_are_ambiguous() {
+        let dir = tempdir().unwrap();
+        let files = [("a.md", "---\norder: 1\n---\na"), ("b.md", "---\norder: 1\n---\nb")];
+        
+        let (lenient_files, dirs) = sources(&dir, SourceKind::Rules, &files);
+        let ordered = ExplicitOrder::apply(lenient_files.clone(), &lenient_files, &dirs, false).unwrap();
+        assert_eq!(names(&ordered), vec!["a.md", "b.md"]);
+        
+        let (strict_files, dirs) = sources(&dir, SourceKind::Rules, &files);
+        let message = ExplicitOrder::apply(strict_files.clone(), &strict_files, &dirs, true).unwrap_err().to_string();
+        assert!(message.contains("Ambiguous order 1 shared by a.md, b.md"));
+    }
+}
@@ -0,0 +1,126 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Which part of a file `{{file:...}}` embeds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExcerptSelector {
+    Whole,
+    /// Inclusive, 1-based line range
+    Lines(usize, usize),
+    /// Lines between `cliner:begin NAME` and `cliner:end NAME` markers
+    Region(String),
+}
+
+/// A reference such as `src/api/schema.graphql#L1-40` or `src/config.rs#defaults`,
+/// relative to the project root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileExcerpt {
+    pub path: PathBuf,
+    pub selector: ExcerptSelector,
+}
+
+impl FileExcerpt {
+    pub fn parse(reference: &str) -> Result<Self, String> {
+        let (path, fragment) = match reference.trim().split_once('#') {
+            Some((path, fragment)) => (path, Some(fragment)),
+            None => (reference.trim(), None),
+        };
+        if path.is_empty() {
+            return Err(format!("missing file path in 'file:{}'", reference));
+        }
+        
+        let selector = match fragment {
+            None => ExcerptSelector::Whole,
+            Some(fragment) => match fragment.strip_prefix('L') {
+                Some(range) if range.starts_with(|c: char| c.is_ascii_digit()) => Self::parse_lines(range)
+                    .ok_or_else(|| format!("invalid line range '#{}' in 'file:{}'", fragment, reference))?,
+                _ if fragment.is_empty() => return Err(format!("missing region name in 'file:{}'", reference)),
+                _ => ExcerptSelector::Region(fragment.to_string()),
+            },
+        };
+        
+        Ok(Self { path: PathBuf::from(path), selector })
+    }
+    
+    fn parse_lines(range: &str) -> Option<ExcerptSelector> {
+        let (start, end) = match range.split_once('-') {
+            Some((start, end)) => (start.parse().ok()?, end.strip_prefix('L').unwrap_or(end).parse().ok()?),
+            None => {
+                let line = range.parse().ok()?;
+                (line, line)
+            },
+        };
+        
+        (start >= 1 && start <= end).then_some(ExcerptSelector::Lines(start, end))
+    }
+    
+    /// Reads the excerpt from the file under `root`, which it may not leave.
+    pub fn read(&self, root: &Path) -> Result<String, String> {
+        let display = self.path.display();
+        let path = root
+            .join(&self.path)
+            .canonicalize()
+            .map_err(|_| format!("embedded file '{}' not found", display))?;
+        if !root.canonicalize().is_ok_and(|root| path.starts_with(root)) {
+            return Err(format!("embedded file '{}' is outside the project", display));
+        }
+        
+        let content = fs::read_to_string(&path).map_err(|e| format!("cannot read embedded file '{}': {}", display, e))?;
+        let lines: Vec<&str> = content.lines().collect();
+        
+        let selected = match &self.selector {
+            ExcerptSelector::Whole => &lines[..],
+            ExcerptSelector::Lines(start, end) => {
+                if *end > lines.len() {
+                    return Err(format!("'{}' has {} lines, so lines {}-{} don't exist", display, lines.len(), start, end));
+                }
+                &lines[start - 1..*end]
+            },
+            ExcerptSelector::Region(name) => Self::region(&lines, name)
+                .ok_or_else(|| format!("region '{}' not found in '{}'", name, display))?,
+        };
+        
+        Ok(selected.join("\n"))
+    }
+    
+    fn region<'a>(lines: &'a [&'a str], name: &str) -> Option<&'a [&'a str]> {
+        let is_marker = |line: &str, keyword: &str| {
+            line.split_once(keyword).is_some_and(|(_, rest)| rest.split_whitespace().next() == Some(name))
+        };
+        
+        let start = lines.iter().position(|line| is_marker(line, "cliner:begin"))? + 1;
+        let length = lines[start..].iter().position(|line| is_marker(line, "cliner:end"))?;
+        Some(&lines[start..start + length])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+    
+    #[test]
+    fn test_parse() {
+        assert_eq!(FileExcerpt::parse("schema.graphql").unwrap().selector, ExcerptSelector::Whole);
+        assert_eq!(FileExcerpt::parse("src/a.rs#L1-40").unwrap().selector, ExcerptSelector::Lines(1, 40));
+        assert_eq!(FileExcerpt::parse("src/a.rs#L3").unwrap().selector, ExcerptSelector::Lines(3, 3));
+        assert_eq!(FileExcerpt::parse("src/a.rs#L3-L5").unwrap().selector, ExcerptSelector::Lines(3, 5));
+        assert_eq!(FileExcerpt::parse("src/a.rs#defaults").unwrap().selector, ExcerptSelector::Region("defaults".to_string()));
+        assert!(FileExcerpt::parse("src/a.rs#L5-2").unwrap_err().contains("invalid line range '#L5-2'"));
+        assert!(FileExcerpt::parse("#L1").is_err());
+    }
+    
+    #[test]
+    fn test_read() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("config.rs"), "fn a() {}\n// cliner:begin defaults\nconst X: u8 = 1;\nconst Y: u8 = 2;\n// cliner:end defaults\n").unwrap();
+        let read = |reference: &str| FileExcerpt::parse(reference).unwrap().read(dir.path());
+        
+        assert_eq!(read("config.rs#L1").unwrap(), "fn a() {}");
+        assert_eq!(read("config.rs#defaults").unwrap(), "const X: u8 = 1;\nconst Y: u8 = 2;");
+        assert!(read("config.rs#L4-9").unwrap_err().contains("has 5 lines"));
+        assert!(read("config.rs#missing").unwrap_err().contains("region 'missing' not found"));
+        assert!(read("other.rs").unwrap_err().contains("embedded file 'other.rs' not found"));
+        assert!(read("../").unwrap_err().contains("outside the project"));
+    }
+}
@@ -1,26 +1,146 @@
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use serde_json::Value;
-use crate::error::Result;
-use crate::models::Mode;
+use crate::error::{ClinerError, Result};
+use crate::models::{GenerateOptions, Mode, OutputTarget, SkipReason, SkippedFile, SortOrder, SourceDir, SourceFile, SourceFilter, SourceKind, SourceMetadata};
+use crate::processors::{ExplicitOrder, Frontmatter, SourceRenderer};
 use crate::processors::markdown_parser::{MarkdownParser, markdown_to_json};
 
 pub struct FileProcessor;
 
+#[derive(Debug, Default)]
+pub struct CollectedSources {
+    pub files: Vec<SourceFile>,
+    pub skipped: Vec<SkippedFile>,
+}
+
 impl FileProcessor {
-    pub fn collect_sorted_entries(directory: &Path) -> Result<Vec<fs::DirEntry>> {
-        let directory_iterator = fs::read_dir(directory)?;
+    /// Lists the files under `directory`, including those in nested folders, as paths
+    /// relative to `directory` ordered by their full relative path.
+    pub fn collect_sorted_files(directory: &Path, sort: SortOrder) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        Self::collect_files_recursive(directory, Path::new(""), &mut files)?;
         
-        let mut directory_entries = Vec::new();
-        for entry_result in directory_iterator {
-            match entry_result {
-                Ok(entry) => directory_entries.push(entry),
+        files.sort_by(|a, b| sort.compare_paths(a, b));
+        Ok(files)
+    }
+    
+    fn collect_files_recursive(root: &Path, relative_dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+        for entry_result in fs::read_dir(root.join(relative_dir))? {
+            let Ok(entry) = entry_result else {
+                continue;
+            };
+            let relative_path = relative_dir.join(entry.file_name());
+            
+            // Symlinked directories are not followed, so a link back up the tree cannot loop.
+            match entry.file_type() {
+                Ok(file_type) if file_type.is_dir() => Self::collect_files_recursive(root, &relative_path, files)?,
+                Ok(_) if entry.path().is_dir() => continue,
+                Ok(_) => files.push(relative_path),
                 Err(_) => continue,
             }
         }
         
-        directory_entries.sort_by(|a, b| a.file_name().cmp(&b.file_name()));
-        Ok(directory_entries)
+        Ok(())
+    }
+    
+    /// Collects files from every layer, letting a file in a later layer replace one with
+    /// the same relative path in an earlier layer. Missing layer directories are skipped,
+    /// and files rejected by `filter` are returned separately.
+    pub fn collect_layered_sources(
+        source_dirs: &[SourceDir],
+        kind: SourceKind,
+        sort: SortOrder,
+        filter: &SourceFilter,
+    ) -> Result<CollectedSources> {
+        let mut sources: Vec<SourceFile> = Vec::new();
+        let mut skipped = Vec::new();
+        
+        for source_dir in source_dirs {
+            if !source_dir.path.is_dir() {
+                continue;
+            }
+            
+            for relative_path in Self::collect_sorted_files(&source_dir.path, sort)? {
+                let path = source_dir.path.join(&relative_path);
+                if let Some(reason) = filter.check(kind, &relative_path) {
+                    skipped.push(SkippedFile { path, reason });
+                    continue;
+                }
+                
+                let source = SourceFile::new(relative_path, path, source_dir.layer);
+                
+                match sources.iter_mut().find(|existing| existing.name == source.name) {
+                    Some(existing) => {
+                        let mut overrides = std::mem::take(&mut existing.overrides);
+                        overrides.push(existing.layer);
+                        *existing = SourceFile { overrides, ..source };
+                    },
+                    None => sources.push(source),
+                }
+            }
+        }
+        
+        sources.sort_by(|a, b| sort.compare_paths(&a.name, &b.name));
+        Ok(CollectedSources { files: sources, skipped })
+    }
+    
+    /// Collects the sources `generate` uses: filtered, with metadata read, disabled and
+    /// unselected files moved to `skipped`, and explicit ordering applied.
+    pub fn select_sources(source_dirs: &[SourceDir], kind: SourceKind, options: &GenerateOptions) -> Result<CollectedSources> {
+        let collected = Self::collect_layered_sources(source_dirs, kind, options.sort, &options.filter)?;
+        let mut skipped = collected.skipped;
+        
+        let sources = Self::read_metadata(collected.files, kind, options.strict)?;
+        let mut enabled = Vec::new();
+        for source in sources.iter().cloned() {
+            if !source.metadata.enabled {
+                skipped.push(SkippedFile { path: source.path, reason: SkipReason::Disabled });
+            } else if !options.is_selected(&source.tags()) {
+                skipped.push(SkippedFile { path: source.path, reason: SkipReason::NotSelected });
+            } else {
+                enabled.push(source);
+            }
+        }
+        
+        let files = ExplicitOrder::apply(enabled, &sources, source_dirs, options.strict)?;
+        Ok(CollectedSources { files, skipped })
+    }
+    
+    /// Reads rule frontmatter, or the `order:` and `tags:` lines of a mode header, into each source's metadata.
+    pub fn read_metadata(mut sources: Vec<SourceFile>, kind: SourceKind, strict: bool) -> Result<Vec<SourceFile>> {
+        for source in &mut sources {
+            let Ok(content) = Self::read_file_content(&source.path) else {
+                continue;
+            };
+            
+            match Self::parse_metadata(kind, &content) {
+                Ok(metadata) => source.metadata = metadata,
+                Err(e) if strict => return Err(Self::with_file_context(e, &source.path)),
+                Err(e) => eprintln!("Warning: Ignoring invalid metadata in {}: {}", source.path.display(), e),
+            }
+        }
+        
+        Ok(sources)
+    }
+    
+    fn parse_metadata(kind: SourceKind, content: &str) -> Result<SourceMetadata> {
+        match kind {
+            SourceKind::Rules => match Frontmatter::split(content).0 {
+                Some(frontmatter) if !frontmatter.trim().is_empty() => Ok(serde_yaml::from_str(frontmatter)?),
+                _ => Ok(SourceMetadata::default()),
+            },
+            SourceKind::Modes => {
+                let order = match MarkdownParser::extract_header_field(content, "order:") {
+                    Some(value) => Some(value.parse().map_err(|_| {
+                        ClinerError::InvalidFormat(format!("order must be an integer, got '{}'", value))
+                    })?),
+                    None => None,
+                };
+                let tags = MarkdownParser::extract_header_list(content, "tags:");
+                Ok(SourceMetadata { order, tags, ..SourceMetadata::default() })
+            },
+        }
     }
     
     pub fn read_file_content(path: &Path) -> Result<String> {
@@ -50,40 +170,62 @@ impl FileProcessor {
         modes
     }
     
-    pub fn convert_entries_to_json(entries: Vec<fs::DirEntry>) -> Vec<Value> {
+    /// Rendering errors always fail, even without `strict`, since they are mistakes in the sources.
+    pub fn convert_entries_to_json(entries: Vec<SourceFile>, renderer: &SourceRenderer, strict: bool) -> Result<Vec<Value>> {
         let mut json_values = Vec::new();
         
         for entry in entries {
-            let file_path = entry.path();
+            let file_path = entry.path;
             let file_content = match Self::read_file_content(&file_path) {
                 Ok(text) => text,
+                Err(e) if strict => return Err(e),
                 Err(_) => continue,
             };
+            let file_content = renderer.render(&file_content, &file_path, OutputTarget::Roomodes)?;
             
             match markdown_to_json(&file_content) {
                 Ok(json_value) => json_values.push(json_value),
+                Err(e) if strict => return Err(Self::with_file_context(e, &file_path)),
                 Err(_) => eprintln!("Warning: Skipping invalid Markdown in {}", file_path.display()),
             }
         }
         
-        json_values
+        Ok(json_values)
     }
     
-    pub fn concatenate_entries(entries: Vec<fs::DirEntry>) -> String {
+    /// Joins rendered rules, each followed by `separator`.
+    pub fn concatenate_rendered<'a>(contents: impl IntoIterator<Item = &'a str>, separator: &str) -> String {
         let mut concatenated_content = String::new();
         
+        for content in contents {
+            concatenated_content.push_str(content);
+            concatenated_content.push_str(separator);
+        }
+        
+        concatenated_content
+    }
+    
+    /// Reads and renders each rule for `.clinerules`, without its frontmatter.
+    pub fn render_rules(entries: Vec<SourceFile>, renderer: &SourceRenderer, strict: bool) -> Result<Vec<(SourceFile, String)>> {
+        let mut rendered = Vec::new();
+        
         for entry in entries {
-            let file_path = entry.path();
-            let file_content = match Self::read_file_content(&file_path) {
+            let file_content = match Self::read_file_content(&entry.path) {
                 Ok(content) => content,
+                Err(e) if strict => return Err(e),
                 Err(_) => continue,
             };
+            let file_content = renderer.render(&file_content, &entry.path, OutputTarget::Clinerules)?;
             
-            concatenated_content.push_str(&file_content);
-            concatenated_content.push_str("\n");
+            let content = Frontmatter::strip(&file_content).to_string();
+            rendered.push((entry, content));
         }
         
-        concatenated_content
+        Ok(rendered)
+    }
+    
+    fn with_file_context(error: ClinerError, file_path: &Path) -> ClinerError {
+        ClinerError::InvalidFormat(format!("{}: {}", file_path.display(), error))
     }
 }
 
@@ -92,9 +234,14 @@ mod tests {
     use super::*;
     use std::io::Write;
     use std::fs::File;
-    use std::path::PathBuf;
     use tempfile::{tempdir, TempDir};
+    use crate::models::{ClinePaths, SourceLayer, TagExpression};
 
+    fn concatenate_entries(entries: Vec<SourceFile>, separator: &str, renderer: &SourceRenderer, strict: bool) -> Result<String> {
+        let rendered = FileProcessor::render_rules(entries, renderer, strict)?;
+        Ok(FileProcessor::concatenate_rendered(rendered.iter().map(|(_, content)| content.as_str()), separator))
+    }
+    
     fn create_temp_file(dir: &TempDir, filename: &str, content: &str) -> PathBuf {
         let file_path = dir.path().join(filename);
         let mut file = File::create(&file_path).unwrap();
@@ -102,6 +249,13 @@ mod tests {
         file_path
     }
 
+    fn project_sources(dir: &TempDir) -> Vec<SourceFile> {
+        let source_dirs = [SourceDir { layer: SourceLayer::Project, path: dir.path().to_path_buf() }];
+        FileProcessor::collect_layered_sources(&source_dirs, SourceKind::Rules, SortOrder::Lexical, &SourceFilter::default())
+            .unwrap()
+            .files
+    }
+    
     #[test]
     fn test_read_file_content() {
         let temp_dir = tempdir().unwrap();
@@ -124,4 +278,202 @@ mod tests {
         create_temp_file(&temp_dir, "file1.txt", "内容1");
         create_temp_file(&temp_dir, "file2.txt", "内容2");
     }
+    
+    #[test]
+    fn test_concatenate_entries_with_separator() {
+        let temp_dir = tempdir().unwrap();
+        create_temp_file(&temp_dir, "b.md", "second");
+        create_temp_file(&temp_dir, "a.md", "first");
+        
+        let entries = project_sources(&temp_dir);
+        let content = concatenate_entries(entries, "\n\n", &SourceRenderer::default(), false).unwrap();
+        
+        assert_eq!(content, "first\n\nsecond\n\n");
+    }
+    
+    #[test]
+    fn test_collect_layered_sources_nested() {
+        let temp_dir = tempdir().unwrap();
+        fs::create_dir_all(temp_dir.path().join("backend").join("api")).unwrap();
+        fs::create_dir_all(temp_dir.path().join("frontend")).unwrap();
+        create_temp_file(&temp_dir, "frontend/00.md", "frontend");
+        create_temp_file(&temp_dir, "backend/api/00.md", "api");
+        create_temp_file(&temp_dir, "backend/10.md", "backend");
+        create_temp_file(&temp_dir, "00_general.md", "general");
+        
+        let names: Vec<PathBuf> = project_sources(&temp_dir).into_iter().map(|source| source.name).collect();
+        
+        assert_eq!(names, vec![
+            PathBuf::from("00_general.md"),
+            PathBuf::from("backend/10.md"),
+            PathBuf::from("backend/api/00.md"),
+            PathBuf::from("frontend/00.md"),
+        ]);
+        
+        let content = concatenate_entries(project_sources(&temp_dir), "\n", &SourceRenderer::default(), true).unwrap();
+        assert_eq!(content, "general\nbackend\napi\nfrontend\n");
+    }
+    
+    #[test]
+    fn test_concatenate_entries_strips_frontmatter() {
+        let temp_dir = tempdir().unwrap();
+        create_temp_file(&temp_dir, "00_style.md", "---\norder: 1\n---\n# Style");
+        
+        let content = concatenate_entries(project_sources(&temp_dir), "\n", &SourceRenderer::default(), true).unwrap();
+        
+        assert_eq!(content, "# Style\n");
+    }
+    
+    #[cfg(unix)]
+    #[test]
+    fn test_concatenate_entries_follows_symlinks() {
+        let temp_dir = tempdir().unwrap();
+        let global_dir = tempdir().unwrap();
+        let global_rule = create_temp_file(&global_dir, "style.md", "global style");
+        std::os::unix::fs::symlink(&global_rule, temp_dir.path().join("00_style.md")).unwrap();
+        
+        let entries = project_sources(&temp_dir);
+        let content = concatenate_entries(entries, "\n", &SourceRenderer::default(), true).unwrap();
+        
+        assert_eq!(content, "global style\n");
+    }
+    
+    #[test]
+    fn test_convert_entries_to_json_strict() {
+        let temp_dir = tempdir().unwrap();
+        create_temp_file(&temp_dir, "00_valid.md", "name: Valid\n---\nRole");
+        create_temp_file(&temp_dir, "01_invalid.md", "no separator here");
+        
+        let entries = project_sources(&temp_dir);
+        let lenient = FileProcessor::convert_entries_to_json(entries, &SourceRenderer::default(), false).unwrap();
+        assert_eq!(lenient.len(), 1);
+        
+        let entries = project_sources(&temp_dir);
+        let strict = FileProcessor::convert_entries_to_json(entries, &SourceRenderer::default(), true);
+        let message = strict.unwrap_err().to_string();
+        assert!(message.contains("01_invalid.md"));
+    }
+    
+    #[test]
+    fn test_collect_layered_sources_overrides_by_name() {
+        let global_dir = tempdir().unwrap();
+        let project_dir = tempdir().unwrap();
+        create_temp_file(&global_dir, "00_style.md", "global style");
+        create_temp_file(&global_dir, "01_commits.md", "global commits");
+        create_temp_file(&project_dir, "00_style.md", "project style");
+        create_temp_file(&project_dir, "02_project.md", "project only");
+        
+        let source_dirs = [
+            SourceDir { layer: SourceLayer::Global, path: global_dir.path().to_path_buf() },
+            SourceDir { layer: SourceLayer::Project, path: project_dir.path().to_path_buf() },
+            SourceDir { layer: SourceLayer::Local, path: project_dir.path().join("missing") },
+        ];
+        let sources = FileProcessor::collect_layered_sources(&source_dirs, SourceKind::Rules, SortOrder::Lexical, &SourceFilter::default())
+            .unwrap()
+            .files;
+        
+        let summary: Vec<(&str, SourceLayer)> = sources
+            .iter()
+            .map(|source| (source.name.to_str().unwrap(), source.layer))
+            .collect();
+        assert_eq!(summary, vec![
+            ("00_style.md", SourceLayer::Project),
+            ("01_commits.md", SourceLayer::Global),
+            ("02_project.md", SourceLayer::Project),
+        ]);
+        assert_eq!(sources[0].overrides, vec![SourceLayer::Global]);
+        
+        let content = concatenate_entries(sources, "\n", &SourceRenderer::default(), false).unwrap();
+        assert_eq!(content, "project style\nglobal commits\nproject only\n");
+    }
+    
+    #[test]
+    fn test_collect_layered_sources_reports_skipped_files() {
+        let temp_dir = tempdir().unwrap();
+        create_temp_file(&temp_dir, "00_style.md", "style");
+        create_temp_file(&temp_dir, "00_style.md.bak", "old style");
+        create_temp_file(&temp_dir, "notes.txt", "notes");
+        
+        let source_dirs = [SourceDir { layer: SourceLayer::Project, path: temp_dir.path().to_path_buf() }];
+        let collected = FileProcessor::collect_layered_sources(&source_dirs, SourceKind::Rules, SortOrder::Lexical, &SourceFilter::default())
+            .unwrap();
+        
+        assert_eq!(collected.files.len(), 1);
+        let skipped: Vec<PathBuf> = collected.skipped.into_iter().map(|skipped| skipped.path).collect();
+        assert_eq!(skipped, vec![temp_dir.path().join("00_style.md.bak"), temp_dir.path().join("notes.txt")]);
+    }
+    
+    #[test]
+    fn test_read_metadata() {
+        let temp_dir = tempdir().unwrap();
+        create_temp_file(&temp_dir, "00_security.md", "---\ntitle: Security\ntags: [backend]\npriority: 5\n---\n# Security");
+        create_temp_file(&temp_dir, "01_plain.md", "# Plain");
+        
+        let sources = FileProcessor::read_metadata(project_sources(&temp_dir), SourceKind::Rules, true).unwrap();
+        
+        assert_eq!(sources[0].metadata.title.as_deref(), Some("Security"));
+        assert_eq!(sources[0].metadata.priority, Some(5));
+        assert_eq!(sources[0].tags(), vec!["backend"]);
+        assert_eq!(sources[1].metadata, SourceMetadata::default());
+    }
+    
+    #[test]
+    fn test_read_metadata_invalid() {
+        let temp_dir = tempdir().unwrap();
+        create_temp_file(&temp_dir, "00.md", "---\norder: first\n---\nrule");
+        create_temp_file(&temp_dir, "01.md", "name: Mode\norder: first\n---\nmode");
+        
+        let message = FileProcessor::read_metadata(project_sources(&temp_dir), SourceKind::Rules, true).unwrap_err().to_string();
+        assert!(message.contains("00.md"));
+        assert!(message.contains("order"));
+        
+        let lenient = FileProcessor::read_metadata(project_sources(&temp_dir), SourceKind::Modes, false).unwrap();
+        assert_eq!(lenient[1].metadata.order, None);
+    }
+    
+    #[test]
+    fn test_select_sources_skips_disabled_rules() {
+        let temp_dir = tempdir().unwrap();
+        create_temp_file(&temp_dir, "00_old.md", "---\nenabled: false\n---\nold");
+        create_temp_file(&temp_dir, "01_current.md", "current");
+        
+        let source_dirs = [SourceDir { layer: SourceLayer::Project, path: temp_dir.path().to_path_buf() }];
+        let selected = FileProcessor::select_sources(&source_dirs, SourceKind::Rules, &GenerateOptions::default()).unwrap();
+        
+        assert_eq!(selected.files.len(), 1);
+        assert_eq!(selected.skipped, vec![SkippedFile { path: temp_dir.path().join("00_old.md"), reason: SkipReason::Disabled }]);
+    }
+    
+    #[test]
+    fn test_select_sources_by_tags() {
+        let temp_dir = tempdir().unwrap();
+        fs::create_dir(temp_dir.path().join("legacy")).unwrap();
+        create_temp_file(&temp_dir, "legacy/old.md", "old");
+        create_temp_file(&temp_dir, "rust.md", "---\ntags: [rust]\n---\nrust");
+        create_temp_file(&temp_dir, "style.md", "style");
+        
+        let source_dirs = [SourceDir { layer: SourceLayer::Project, path: temp_dir.path().to_path_buf() }];
+        let options = GenerateOptions { select: Some(TagExpression::parse("!legacy").unwrap()), ..GenerateOptions::default() };
+        let selected = FileProcessor::select_sources(&source_dirs, SourceKind::Rules, &options).unwrap();
+        
+        let names: Vec<_> = selected.files.iter().map(|source| source.name.clone()).collect();
+        assert_eq!(names, vec![PathBuf::from("rust.md"), PathBuf::from("style.md")]);
+        assert_eq!(selected.skipped[0].reason, SkipReason::NotSelected);
+    }
+    
+    #[test]
+    fn test_concatenate_entries_renders_content() {
+        let temp_dir = tempdir().unwrap();
+        let rules_dir = temp_dir.path().join(".cline/rules");
+        fs::create_dir_all(&rules_dir).unwrap();
+        fs::write(temp_dir.path().join(".cline/partial.md"), "Ask {{team}}.\n").unwrap();
+        fs::write(rules_dir.join("00.md"), "---\ntags: [x]\n---\n# Rule\n<!-- @include ../partial.md -->\n").unwrap();
+        let options = GenerateOptions { variables: [("team".to_string(), "platform".to_string())].into(), ..GenerateOptions::default() };
+        let renderer = SourceRenderer::new(&ClinePaths::from_root(temp_dir.path()), &options);
+        let entries = vec![SourceFile::new(PathBuf::from("00.md"), rules_dir.join("00.md"), SourceLayer::Project)];
+        
+        let content = concatenate_entries(entries, "", &renderer, false).unwrap();
+        
+        assert_eq!(content, "# Rule\nAsk platform.\n");
+    }
 }
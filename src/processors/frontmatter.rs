@@ -0,0 +1,44 @@
+/// Splits a leading YAML block delimited by `---` lines off a rule file.
+pub struct Frontmatter;
+
+impl Frontmatter {
+    /// Returns the frontmatter (without delimiters), if any, and the remaining body.
+    pub fn split(content: &str) -> (Option<&str>, &str) {
+        let Some(rest) = content.strip_prefix("---\n").or_else(|| content.strip_prefix("---\r\n")) else {
+            return (None, content);
+        };
+        
+        let mut offset = 0;
+        for line in rest.split_inclusive('\n') {
+            if line.trim_end() == "---" {
+                return (Some(&rest[..offset]), &rest[offset + line.len()..]);
+            }
+            offset += line.len();
+        }
+        
+        (None, content)
+    }
+    
+    pub fn strip(content: &str) -> &str {
+        Self::split(content).1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_split() {
+        let (frontmatter, body) = Frontmatter::split("---\norder: 2\n---\n# Style\n");
+        
+        assert_eq!(frontmatter, Some("order: 2\n"));
+        assert_eq!(body, "# Style\n");
+    }
+    
+    #[test]
+    fn test_split_without_frontmatter() {
+        assert_eq!(Frontmatter::split("# Style\n---\nmore"), (None, "# Style\n---\nmore"));
+        assert_eq!(Frontmatter::split("---\nnever closed\n"), (None, "---\nnever closed\n"));
+    }
+}
@@ -0,0 +1,225 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::error::{ClinerError, Result};
+use crate::processors::{CodeFence, Frontmatter};
+
+/// Rewrites one file before its includes are expanded, keeping the source line number of every line.
+pub type Transform<'a> = dyn Fn(&str, &Path) -> Result<Vec<(usize, String)>> + 'a;
+
+/// Expands `<!-- @include path -->` lines with the contents of the named file, relative to the
+/// including file and inside its sandbox root.
+#[derive(Debug, Clone, Default)]
+pub struct IncludeResolver {
+    roots: Vec<PathBuf>,
+}
+
+impl IncludeResolver {
+    /// Roots that don't exist are dropped; the rest are canonicalized so symlinks can't escape them.
+    pub fn new(roots: &[PathBuf]) -> Self {
+        let roots = roots.iter().filter_map(|root| root.canonicalize().ok()).collect();
+        Self { roots }
+    }
+    
+    pub fn resolve(&self, content: &str, path: &Path, transform: &Transform) -> Result<String> {
+        let mut stack = vec![path.canonicalize().unwrap_or_else(|_| path.to_path_buf())];
+        self.resolve_nested(content, path, transform, &mut stack)
+    }
+    
+    fn resolve_nested(&self, content: &str, path: &Path, transform: &Transform, stack: &mut Vec<PathBuf>) -> Result<String> {
+        let lines = transform(content, path)?;
+        let mut resolved = String::with_capacity(content.len());
+        
+        let mut fence = CodeFence::default();
+        for (number, line) in &lines {
+            let in_fence = fence.update(line);
+            let Some(target) = Self::parse_directive(line).filter(|_| !in_fence) else {
+                resolved.push_str(line);
+                continue;
+            };
+            
+            let error = |message: String| self.error_at(path, *number, message);
+            let included_path = self.locate(path, target).map_err(error)?;
+            
+            if let Some(start) = stack.iter().position(|entry| *entry == included_path) {
+                let mut cycle: Vec<String> = stack[start..].iter().map(|entry| self.display_path(entry)).collect();
+                cycle.push(self.display_path(&included_path));
+                return Err(error(format!("include cycle {}", cycle.join(" -> "))));
+            }
+            
+            let included = fs::read_to_string(&included_path)
+                .map_err(|e| error(format!("cannot read included file '{}': {}", target, e)))?;
+            
+            stack.push(included_path.clone());
+            let expanded = self.resolve_nested(Frontmatter::strip(&included), &included_path, transform, stack)?;
+            stack.pop();
+            
+            resolved.push_str(expanded.trim_end_matches('\n'));
+            if line.ends_with('\n') {
+                resolved.push('\n');
+            }
+        }
+        
+        Ok(resolved)
+    }
+    
+    /// Returns the path of an include directive such as `<!-- @include ../partials/style.md -->`.
+    pub fn parse_directive(line: &str) -> Option<&str> {
+        parse_comment_directive(line, "@include").filter(|target| !target.is_empty())
+    }
+    
+    fn locate(&self, including_path: &Path, target: &str) -> std::result::Result<PathBuf, String> {
+        let directory = including_path.parent().unwrap_or(Path::new(""));
+        let included_path = directory
+            .join(target)
+            .canonicalize()
+            .map_err(|_| format!("included file '{}' not found", target))?;
+        
+        // A file may only include from its own root, so a project rule can't reach into the
+        // global or organization directory, nor the other way around.
+        match self.root_of(including_path) {
+            Some(root) if !included_path.starts_with(root) => {
+                return Err(format!("included file '{}' is outside {}", target, root.display()));
+            },
+            None if !self.roots.iter().any(|root| included_path.starts_with(root)) => {
+                return Err(format!("included file '{}' is outside .cline", target));
+            },
+            _ => {},
+        }
+        if !included_path.is_file() {
+            return Err(format!("included path '{}' is not a file", target));
+        }
+        
+        Ok(included_path)
+    }
+    
+    /// The innermost root holding `path`.
+    fn root_of(&self, path: &Path) -> Option<&PathBuf> {
+        let path = path.parent()?.canonicalize().ok()?;
+        self.roots
+            .iter()
+            .filter(|root| path.starts_with(root))
+            .max_by_key(|root| root.components().count())
+    }
+    
+    pub fn error_at(&self, path: &Path, line: usize, message: String) -> ClinerError {
+        ClinerError::InvalidFormat(format!("{}:{}: {}", self.display_path(path), line, message))
+    }
+    
+    /// Shortens a path to be relative to its sandbox root for error messages.
+    pub fn display_path(&self, path: &Path) -> String {
+        self.roots
+            .iter()
+            .find_map(|root| path.strip_prefix(root).ok())
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    }
+}
+
+/// Returns the argument of a line holding only `<!-- keyword argument -->`, which may be empty.
+pub fn parse_comment_directive<'a>(line: &'a str, keyword: &str) -> Option<&'a str> {
+    let comment = line.trim().strip_prefix("<!--")?.strip_suffix("-->")?.trim();
+    let argument = comment.strip_prefix(keyword)?;
+    if !argument.is_empty() && !argument.starts_with(char::is_whitespace) {
+        return None;
+    }
+    
+    Some(argument.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::{tempdir, TempDir};
+    
+    fn write(dir: &TempDir, name: &str, content: &str) -> PathBuf {
+        let path = dir.path().join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, content).unwrap();
+        path
+    }
+    
+    fn unchanged(content: &str, _path: &Path) -> Result<Vec<(usize, String)>> {
+        Ok(content.split_inclusive('\n').enumerate().map(|(index, line)| (index + 1, line.to_string())).collect())
+    }
+    
+    fn resolve(dir: &TempDir, path: &Path) -> Result<String> {
+        let content = fs::read_to_string(path).unwrap();
+        IncludeResolver::new(&[dir.path().to_path_buf()]).resolve(&content, path, &unchanged)
+    }
+    
+    #[test]
+    fn test_parse_directive() {
+        assert_eq!(IncludeResolver::parse_directive("<!-- @include ../partials/style.md -->\n"), Some("../partials/style.md"));
+        assert_eq!(IncludeResolver::parse_directive("  <!--@include a.md-->"), Some("a.md"));
+        assert_eq!(IncludeResolver::parse_directive("<!-- @includes a.md -->"), None);
+        assert_eq!(IncludeResolver::parse_directive("see <!-- @include a.md --> here"), None);
+    }
+    
+    #[test]
+    fn test_resolve_nested_includes() {
+        let dir = tempdir().unwrap();
+        write(&dir, "partials/style.md", "---\ntitle: Style\n---\nUse rustfmt.\n<!-- @include commits.md -->\n");
+        write(&dir, "partials/commits.md", "Write short commit subjects.\n");
+        let rule = write(&dir, "rules/00.md", "# Rules\n<!-- @include ../partials/style.md -->\nEnd\n");
+        
+        let content = resolve(&dir, &rule).unwrap();
+        
+        assert_eq!(content, "# Rules\nUse rustfmt.\nWrite short commit subjects.\nEnd\n");
+    }
+    
+    #[test]
+    fn test_cycle_is_an_error() {
+        let dir = tempdir().unwrap();
+        write(&dir, "partials/a.md", "<!-- @include b.md -->\n");
+        write(&dir, "partials/b.md", "b\n<!-- @include a.md -->\n");
+        let rule = write(&dir, "rules/00.md", "<!-- @include ../partials/a.md -->\n");
+        
+        let message = resolve(&dir, &rule).unwrap_err().to_string();
+        
+        assert!(message.contains("b.md:2: include cycle partials/a.md -> partials/b.md -> partials/a.md"), "{}", message);
+    }
+    
+    #[test]
+    fn test_missing_and_outside_includes_name_the_line() {
+        let outer = tempdir().unwrap();
+        write(&outer, "secret.md", "secret");
+        let dir_path = outer.path().join(".cline");
+        let rule = dir_path.join("rules/00.md");
+        fs::create_dir_all(rule.parent().unwrap()).unwrap();
+        let resolver = IncludeResolver::new(std::slice::from_ref(&dir_path));
+        
+        let missing = resolver.resolve("# Rules\n<!-- @include missing.md -->\n", &rule, &unchanged).unwrap_err().to_string();
+        assert!(missing.contains("00.md:2: included file 'missing.md' not found"), "{}", missing);
+        
+        let outside = resolver.resolve("<!-- @include ../../secret.md -->\n", &rule, &unchanged).unwrap_err().to_string();
+        assert!(outside.contains(&format!("00.md:1: included file '../../secret.md' is outside {}", dir_path.canonicalize().unwrap().display())), "{}", outside);
+    }
+    
+    #[test]
+    fn test_includes_stay_in_their_own_root() {
+        let project = tempdir().unwrap();
+        let global = tempdir().unwrap();
+        write(&project, "partials/project.md", "project\n");
+        write(&global, "partials/global.md", "global\n");
+        let project_rule = write(&project, "rules/00.md", "");
+        let global_rule = write(&global, "rules/00.md", "");
+        let resolver = IncludeResolver::new(&[project.path().to_path_buf(), global.path().to_path_buf()]);
+        let include = |rule: &Path, target: &Path| {
+            let directive = format!("<!-- @include {} -->\n", target.display());
+            resolver.resolve(&directive, rule, &unchanged)
+        };
+        
+        assert_eq!(include(&global_rule, Path::new("../partials/global.md")).unwrap(), "global\n");
+        assert!(include(&project_rule, &global.path().join("partials/global.md")).unwrap_err().to_string().contains("is outside"));
+        assert!(include(&global_rule, &project.path().join("partials/project.md")).unwrap_err().to_string().contains("is outside"));
+    }
+    
+    #[test]
+    fn test_directives_in_code_blocks_are_kept() {
+        let dir = tempdir().unwrap();
+        let rule = write(&dir, "rules/00.md", "```markdown\n<!-- @include missing.md -->\n```\n");
+        
+        assert_eq!(resolve(&dir, &rule).unwrap(), "```markdown\n<!-- @include missing.md -->\n```\n");
+    }
+}
@@ -30,6 +30,32 @@ impl MarkdownParser {
         ))
     }
     
+    /// Looks up a `key: value` line in the header before the first `---`.
+    pub fn extract_header_field(markdown_content: &str, field_prefix: &str) -> Option<String> {
+        let header = markdown_content.split("---").next()?;
+        let lines: Vec<String> = header.lines().map(String::from).collect();
+        Self::extract_field_value(&lines, field_prefix)
+    }
+    
+    /// Looks up a list in the header, written either as `key: a, b` or as `- item` lines after `key:`.
+    pub fn extract_header_list(markdown_content: &str, field_prefix: &str) -> Vec<String> {
+        let Some(header) = markdown_content.split("---").next() else {
+            return Vec::new();
+        };
+        let lines: Vec<String> = header.lines().map(String::from).collect();
+        
+        match Self::extract_field_value(&lines, field_prefix) {
+            Some(inline) if !inline.is_empty() => inline
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .split(',')
+                .map(|item| item.trim().to_string())
+                .filter(|item| !item.is_empty())
+                .collect(),
+            _ => Self::extract_list(&lines, field_prefix),
+        }
+    }
+    
     pub fn parse_to_json(markdown_content: &str) -> Result<Value> {
         let mode = Self::parse_to_mode(markdown_content)?;
         mode.to_json()
@@ -105,14 +131,17 @@ impl MarkdownParser {
     }
 
     fn extract_groups(lines: &[String]) -> Vec<String> {
-        let groups_section_marker = "groups:";
-        let mut group_items = Vec::new();
+        Self::extract_list(lines, "groups:")
+    }
+    
+    fn extract_list(lines: &[String], section_marker: &str) -> Vec<String> {
+        let mut items = Vec::new();
         let mut currently_in_section = false;
         
         for line in lines {
             let trimmed_line = line.trim();
             
-            if trimmed_line == groups_section_marker {
+            if trimmed_line == section_marker {
                 currently_in_section = true;
                 continue;
             }
@@ -122,8 +151,8 @@ impl MarkdownParser {
             }
             
             if line.starts_with("- ") {
-                let group_name = line.replace("- ", "").trim().to_string();
-                group_items.push(group_name);
+                let item = line.replace("- ", "").trim().to_string();
+                items.push(item);
                 continue;
             }
             
@@ -132,7 +161,7 @@ impl MarkdownParser {
             }
         }
         
-        group_items
+        items
     }
 
 }
@@ -174,6 +203,16 @@ This is a detailed description of the test role.".to_string()
         assert!(mode.role_definition.contains("Test Role Definition"));
     }
 
+    #[test]
+    fn test_extract_header_list() {
+        let listed = "name: A\ntags:\n- backend\n- api\n---\nA";
+        let inline = "name: A\ntags: [backend, api]\n---\nA";
+        
+        assert_eq!(MarkdownParser::extract_header_list(listed, "tags:"), vec!["backend", "api"]);
+        assert_eq!(MarkdownParser::extract_header_list(inline, "tags:"), vec!["backend", "api"]);
+        assert!(MarkdownParser::extract_header_list("name: A\n---\nA", "tags:").is_empty());
+    }
+    
     #[test]
     fn test_extract_mode_name() {
         let lines = vec![
@@ -1,4 +1,26 @@
 pub mod file_processor;
 pub mod markdown_parser;
+pub mod mode_merger;
+pub mod source_checkout;
+pub mod three_way_merge;
+pub mod frontmatter;
+pub mod explicit_order;
+pub mod include_resolver;
+pub mod source_renderer;
+pub mod file_excerpt;
+pub mod command_runner;
+pub mod project_context;
+pub mod code_fence;
 
 pub use file_processor::FileProcessor;
+pub use mode_merger::ModeMerger;
+pub use source_checkout::SourceCheckout;
+pub use three_way_merge::ThreeWayMerge;
+pub use frontmatter::Frontmatter;
+pub use explicit_order::ExplicitOrder;
+pub use include_resolver::IncludeResolver;
+pub use source_renderer::SourceRenderer;
+pub use file_excerpt::FileExcerpt;
+pub use command_runner::CommandRunner;
+pub use project_context::ProjectContext;
+pub use code_fence::CodeFence;
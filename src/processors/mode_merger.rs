@@ -0,0 +1,223 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use serde_json::Value;
+use crate::error::{ClinerError, Result};
+
+pub const MANAGED_KEY: &str = "managed_by";
+pub const MANAGED_VALUE: &str = "cliner";
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct MergeSummary {
+    pub updated: usize,
+    pub added: usize,
+    pub removed: usize,
+    pub kept: usize,
+}
+
+pub struct ModeMerger;
+
+impl ModeMerger {
+    pub fn read_existing_modes(path: &Path) -> Result<Vec<Value>> {
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        
+        let content = fs::read_to_string(path)?;
+        if content.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        
+        let document: Value = match serde_json::from_str(&content) {
+            Ok(value) => value,
+            Err(_) => serde_yaml::from_str(&content)?,
+        };
+        
+        match document.get("customModes") {
+            Some(Value::Array(modes)) => Ok(modes.clone()),
+            Some(_) => Err(ClinerError::InvalidFormat(format!("'customModes' in {} is not a list", path.display()))),
+            None => Ok(Vec::new()),
+        }
+    }
+    
+    pub fn mark_managed(json_values: &mut [Value]) {
+        for json_value in json_values.iter_mut() {
+            if let Value::Object(fields) = json_value {
+                fields.insert(MANAGED_KEY.to_string(), Value::String(MANAGED_VALUE.to_string()));
+            }
+        }
+    }
+    
+    pub fn is_managed(json_value: &Value) -> bool {
+        json_value.get(MANAGED_KEY).and_then(Value::as_str) == Some(MANAGED_VALUE)
+    }
+    
+    pub fn merge(existing: Vec<Value>, generated: Vec<Value>) -> (Vec<Value>, MergeSummary) {
+        let mut summary = MergeSummary::default();
+        let mut merged = Vec::new();
+        let mut emitted_slugs = HashSet::new();
+        
+        for existing_mode in existing {
+            let slug = Self::slug_of(&existing_mode);
+            let replacement = slug.and_then(|slug| {
+                generated.iter().find(|mode| Self::slug_of(mode) == Some(slug))
+            });
+            
+            if let Some(generated_mode) = replacement {
+                emitted_slugs.insert(slug.unwrap_or_default().to_string());
+                merged.push(Self::update_in_place(existing_mode, generated_mode));
+                summary.updated += 1;
+                continue;
+            }
+            
+            if Self::is_managed(&existing_mode) {
+                summary.removed += 1;
+                continue;
+            }
+            
+            merged.push(existing_mode);
+            summary.kept += 1;
+        }
+        
+        for generated_mode in generated {
+            let already_emitted = Self::slug_of(&generated_mode)
+                .map(|slug| emitted_slugs.contains(slug))
+                .unwrap_or(false);
+            if already_emitted {
+                continue;
+            }
+            
+            merged.push(generated_mode);
+            summary.added += 1;
+        }
+        
+        (merged, summary)
+    }
+    
+    /// Gives `existing` the fields of `generated` while keeping the order of the keys it
+    /// already has; new keys go last and keys the generated mode lacks are dropped.
+    fn update_in_place(existing: Value, generated: &Value) -> Value {
+        let (Value::Object(mut fields), Value::Object(generated_fields)) = (existing, generated) else {
+            return generated.clone();
+        };
+        
+        fields.retain(|key, _| generated_fields.contains_key(key));
+        for (key, value) in generated_fields {
+            fields.insert(key.clone(), value.clone());
+        }
+        Value::Object(fields)
+    }
+    
+    fn slug_of(json_value: &Value) -> Option<&str> {
+        json_value.get("slug").and_then(Value::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::tempdir;
+    
+    fn managed(slug: &str, role: &str) -> Value {
+        json!({"slug": slug, "role_definition": role, MANAGED_KEY: MANAGED_VALUE})
+    }
+    
+    #[test]
+    fn test_mark_managed() {
+        let mut values = vec![json!({"slug": "a"})];
+        ModeMerger::mark_managed(&mut values);
+        
+        assert!(ModeMerger::is_managed(&values[0]));
+        assert!(!ModeMerger::is_managed(&json!({"slug": "a"})));
+    }
+    
+    #[test]
+    fn test_merge_updates_in_place_and_keeps_custom_modes() {
+        let existing = vec![
+            json!({"slug": "ui-mode", "role_definition": "made in the UI"}),
+            managed("designer", "old"),
+            json!({"slug": "another-ui-mode", "role_definition": "also UI"}),
+        ];
+        let generated = vec![managed("designer", "new")];
+        
+        let (merged, summary) = ModeMerger::merge(existing, generated);
+        
+        let slugs: Vec<&str> = merged.iter().map(|m| m["slug"].as_str().unwrap()).collect();
+        assert_eq!(slugs, vec!["ui-mode", "designer", "another-ui-mode"]);
+        assert_eq!(merged[1]["role_definition"], "new");
+        assert_eq!(summary, MergeSummary { updated: 1, added: 0, removed: 0, kept: 2 });
+    }
+    
+    #[test]
+    fn test_merge_removes_stale_managed_modes_and_appends_new_ones() {
+        let existing = vec![
+            managed("stale", "source deleted"),
+            json!({"slug": "ui-mode"}),
+        ];
+        let generated = vec![managed("fresh", "new mode")];
+        
+        let (merged, summary) = ModeMerger::merge(existing, generated);
+        
+        let slugs: Vec<&str> = merged.iter().map(|m| m["slug"].as_str().unwrap()).collect();
+        assert_eq!(slugs, vec!["ui-mode", "fresh"]);
+        assert_eq!(summary, MergeSummary { updated: 0, added: 1, removed: 1, kept: 1 });
+    }
+    
+    #[test]
+    fn test_merge_replaces_unmarked_mode_with_same_slug() {
+        let existing = vec![json!({"slug": "designer", "role_definition": "hand written"})];
+        let generated = vec![managed("designer", "generated")];
+        
+        let (merged, summary) = ModeMerger::merge(existing, generated);
+        
+        assert_eq!(merged.len(), 1);
+        assert!(ModeMerger::is_managed(&merged[0]));
+        assert_eq!(summary.updated, 1);
+    }
+    
+    #[test]
+    fn test_merge_keeps_key_order() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join(".roomodes");
+        fs::write(&path, r#"{"customModes": [
+            {"slug": "custom", "zeta": 1, "alpha": 2},
+            {"role_definition": "old", "slug": "code", "stale": true, "managed_by": "cliner"}
+        ]}"#).unwrap();
+        let generated = vec![json!({"slug": "code", "groups": ["read"], "role_definition": "new", MANAGED_KEY: MANAGED_VALUE})];
+        
+        let (merged, _) = ModeMerger::merge(ModeMerger::read_existing_modes(&path).unwrap(), generated);
+        
+        let keys = |mode: &Value| mode.as_object().unwrap().keys().cloned().collect::<Vec<_>>();
+        assert_eq!(keys(&merged[0]), vec!["slug", "zeta", "alpha"]);
+        assert_eq!(keys(&merged[1]), vec!["role_definition", "slug", "managed_by", "groups"]);
+        assert_eq!(merged[1]["role_definition"], "new");
+    }
+    
+    #[test]
+    fn test_read_existing_modes_json_and_yaml() {
+        let temp_dir = tempdir().unwrap();
+        
+        let json_path = temp_dir.path().join("json.roomodes");
+        fs::write(&json_path, r#"{"_generatedBy": "Generated by cliner with profile: ci", "customModes": [{"slug": "a"}]}"#).unwrap();
+        let modes = ModeMerger::read_existing_modes(&json_path).unwrap();
+        assert_eq!(modes[0]["slug"], "a");
+        
+        let yaml_path = temp_dir.path().join("yaml.roomodes");
+        fs::write(&yaml_path, "customModes:\n  - slug: b\n").unwrap();
+        let modes = ModeMerger::read_existing_modes(&yaml_path).unwrap();
+        assert_eq!(modes[0]["slug"], "b");
+        
+        let missing_path = temp_dir.path().join("missing.roomodes");
+        assert!(ModeMerger::read_existing_modes(&missing_path).unwrap().is_empty());
+    }
+    
+    #[test]
+    fn test_read_existing_modes_rejects_non_list() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join(".roomodes");
+        fs::write(&path, r#"{"customModes": "nope"}"#).unwrap();
+        
+        assert!(ModeMerger::read_existing_modes(&path).is_err());
+    }
+}
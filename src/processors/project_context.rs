@@ -0,0 +1,264 @@
+use std::fs;
+use std::path::Path;
+use ignore::WalkBuilder;
+use serde_json::Value;
+use crate::error::Result;
+
+/// Directories that are never worth showing in the layout, even without a `.gitignore`.
+const SKIPPED_DIRS: [&str; 8] = ["target", "node_modules", "dist", "build", "venv", "__pycache__", "vendor", "coverage"];
+const TEST_DIRS: [&str; 5] = ["tests", "test", "__tests__", "spec", "specs"];
+
+/// One part of the project context rule; `.cline/context/<key>.md` replaces the generated body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextSection {
+    pub key: &'static str,
+    pub title: &'static str,
+    pub body: String,
+}
+
+/// Builds a "this project uses X, build with Y, tests live in Z" rule from the project's manifests and layout.
+pub struct ProjectContext;
+
+impl ProjectContext {
+    pub fn render(root: &Path, overrides_dir: &Path, depth: usize) -> Result<String> {
+        let mut content = String::from("# Project Context\n");
+        
+        for section in Self::sections(root, depth) {
+            let override_file = overrides_dir.join(format!("{}.md", section.key));
+            let body = if override_file.is_file() {
+                fs::read_to_string(&override_file)?
+            } else {
+                section.body
+            };
+            
+            if body.trim().is_empty() {
+                continue;
+            }
+            content.push_str(&format!("\n## {}\n\n{}\n", section.title, body.trim_end()));
+        }
+        
+        Ok(content)
+    }
+    
+    pub fn sections(root: &Path, depth: usize) -> Vec<ContextSection> {
+        let mut languages = Vec::new();
+        let mut commands = Vec::new();
+        
+        if let Some(manifest) = Self::read_toml(&root.join("Cargo.toml")) {
+            Self::describe_cargo(&manifest, &mut languages, &mut commands);
+        }
+        if let Some(manifest) = Self::read_json(&root.join("package.json")) {
+            Self::describe_package_json(root, &manifest, &mut languages, &mut commands);
+        }
+        if let Some(manifest) = Self::read_toml(&root.join("pyproject.toml")) {
+            Self::describe_pyproject(root, &manifest, &mut languages, &mut commands);
+        }
+        
+        let (tree, test_dirs) = Self::layout(root, depth);
+        let mut layout = String::new();
+        if !tree.is_empty() {
+            layout.push_str(&format!("```\n{}```\n", tree));
+        }
+        if !test_dirs.is_empty() {
+            let test_dirs: Vec<String> = test_dirs.iter().map(|dir| format!("`{}/`", dir)).collect();
+            layout.push_str(&format!("\nTests live in {}.\n", test_dirs.join(", ")));
+        }
+        
+        vec![
+            ContextSection { key: "language", title: "Language", body: Self::bullets(&languages) },
+            ContextSection { key: "build", title: "Build", body: Self::bullets(&commands) },
+            ContextSection { key: "layout", title: "Layout", body: layout },
+        ]
+    }
+    
+    fn describe_cargo(manifest: &toml::Table, languages: &mut Vec<String>, commands: &mut Vec<String>) {
+        let package = manifest.get("package").and_then(toml::Value::as_table);
+        let mut language = String::from("Rust");
+        if let Some(edition) = package.and_then(|package| package.get("edition")).and_then(toml::Value::as_str) {
+            language.push_str(&format!(" (edition {})", edition));
+        }
+        if let Some(name) = package.and_then(|package| package.get("name")).and_then(toml::Value::as_str) {
+            language.push_str(&format!(", crate `{}`", name));
+        }
+        let members = manifest
+            .get("workspace")
+            .and_then(|workspace| workspace.get("members"))
+            .and_then(toml::Value::as_array);
+        if let Some(members) = members {
+            let members: Vec<&str> = members.iter().filter_map(toml::Value::as_str).collect();
+            language.push_str(&format!(", workspace members: {}", members.join(", ")));
+        }
+        languages.push(language);
+        
+        commands.push("Build: `cargo build`".to_string());
+        commands.push("Test: `cargo test`".to_string());
+        commands.push("Lint: `cargo clippy --all-targets`".to_string());
+    }
+    
+    fn describe_package_json(root: &Path, manifest: &Value, languages: &mut Vec<String>, commands: &mut Vec<String>) {
+        let has_dependency = |name: &str| {
+            ["dependencies", "devDependencies"].iter().any(|key| manifest[key].get(name).is_some())
+        };
+        let mut language = if root.join("tsconfig.json").is_file() || has_dependency("typescript") {
+            String::from("TypeScript")
+        } else {
+            String::from("JavaScript")
+        };
+        if let Some(name) = manifest["name"].as_str() {
+            language.push_str(&format!(", package `{}`", name));
+        }
+        languages.push(language);
+        
+        let runner = if root.join("pnpm-lock.yaml").is_file() {
+            "pnpm"
+        } else if root.join("yarn.lock").is_file() {
+            "yarn"
+        } else {
+            "npm"
+        };
+        commands.push(format!("Install: `{} install`", runner));
+        if let Some(scripts) = manifest["scripts"].as_object() {
+            for script in scripts.keys() {
+                commands.push(format!("`{} run {}`", runner, script));
+            }
+        }
+    }
+    
+    fn describe_pyproject(root: &Path, manifest: &toml::Table, languages: &mut Vec<String>, commands: &mut Vec<String>) {
+        let project = manifest.get("project").and_then(toml::Value::as_table);
+        let poetry = manifest.get("tool").and_then(|tool| tool.get("poetry"));
+        let mut language = String::from("Python");
+        if let Some(requires) = project.and_then(|project| project.get("requires-python")).and_then(toml::Value::as_str) {
+            language.push_str(&format!(" ({})", requires));
+        }
+        let name = project.or(poetry.and_then(toml::Value::as_table)).and_then(|table| table.get("name")).and_then(toml::Value::as_str);
+        if let Some(name) = name {
+            language.push_str(&format!(", package `{}`", name));
+        }
+        languages.push(language);
+        
+        let uses_pytest = root.join("tests").is_dir() || manifest.get("tool").and_then(|tool| tool.get("pytest")).is_some();
+        if poetry.is_some() {
+            commands.push("Install: `poetry install`".to_string());
+            if uses_pytest {
+                commands.push("Test: `poetry run pytest`".to_string());
+            }
+        } else {
+            commands.push("Install: `pip install -e .`".to_string());
+            if uses_pytest {
+                commands.push("Test: `pytest`".to_string());
+            }
+        }
+    }
+    
+    /// Directories up to `depth` levels deep, honoring `.gitignore`, and the test directories among them.
+    fn layout(root: &Path, depth: usize) -> (String, Vec<String>) {
+        let mut tree = String::new();
+        let mut test_dirs = Vec::new();
+        if depth == 0 {
+            return (tree, test_dirs);
+        }
+        
+        // An empty root means the current directory, which the walker can't start from.
+        let root = if root.as_os_str().is_empty() { Path::new(".") } else { root };
+        let walker = WalkBuilder::new(root)
+            .max_depth(Some(depth))
+            .require_git(false)
+            .sort_by_file_name(|a, b| a.cmp(b))
+            .filter_entry(|entry| !SKIPPED_DIRS.iter().any(|skipped| entry.file_name() == *skipped))
+            .build();
+        
+        for entry in walker.flatten() {
+            let is_dir = entry.file_type().is_some_and(|file_type| file_type.is_dir());
+            let Ok(relative) = entry.path().strip_prefix(root) else {
+                continue;
+            };
+            if !is_dir || relative.as_os_str().is_empty() {
+                continue;
+            }
+            
+            let name = entry.file_name().to_string_lossy();
+            tree.push_str(&format!("{}{}/\n", "  ".repeat(entry.depth() - 1), name));
+            if TEST_DIRS.contains(&name.as_ref()) {
+                test_dirs.push(relative.to_string_lossy().replace('\\', "/"));
+            }
+        }
+        
+        (tree, test_dirs)
+    }
+    
+    fn bullets(items: &[String]) -> String {
+        items.iter().map(|item| format!("- {}\n", item)).collect()
+    }
+    
+    fn read_toml(path: &Path) -> Option<toml::Table> {
+        fs::read_to_string(path).ok()?.parse().ok()
+    }
+    
+    fn read_json(path: &Path) -> Option<Value> {
+        serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::{tempdir, TempDir};
+    
+    fn write(dir: &TempDir, name: &str, content: &str) {
+        let path = dir.path().join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+    
+    #[test]
+    fn test_rust_project() {
+        let dir = tempdir().unwrap();
+        write(&dir, "Cargo.toml", "[package]\nname = \"demo\"\nedition = \"2021\"\n");
+        write(&dir, "src/models/mod.rs", "");
+        write(&dir, "tests/cli.rs", "");
+        write(&dir, "target/debug/demo", "");
+        
+        let content = ProjectContext::render(dir.path(), &dir.path().join(".cline/context"), 2).unwrap();
+        
+        assert!(content.starts_with("# Project Context\n\n## Language\n\n- Rust (edition 2021), crate `demo`\n"));
+        assert!(content.contains("- Test: `cargo test`\n"));
+        assert!(content.contains("```\nsrc/\n  models/\ntests/\n```\n"));
+        assert!(content.contains("Tests live in `tests/`."));
+        assert!(!content.contains("target/"));
+    }
+    
+    #[test]
+    fn test_package_json_and_pyproject() {
+        let dir = tempdir().unwrap();
+        write(&dir, "package.json", r#"{"name": "web", "scripts": {"build": "vite build", "test": "vitest"}, "devDependencies": {"typescript": "5"}}"#);
+        write(&dir, "yarn.lock", "");
+        write(&dir, "pyproject.toml", "[project]\nname = \"tools\"\nrequires-python = \">=3.11\"\n\n[tool.pytest.ini_options]\n");
+        
+        let sections = ProjectContext::sections(dir.path(), 1);
+        
+        assert_eq!(sections[0].body, "- TypeScript, package `web`\n- Python (>=3.11), package `tools`\n");
+        assert_eq!(
+            sections[1].body,
+            "- Install: `yarn install`\n- `yarn run build`\n- `yarn run test`\n- Install: `pip install -e .`\n- Test: `pytest`\n",
+        );
+    }
+    
+    #[test]
+    fn test_overrides_and_gitignore() {
+        let dir = tempdir().unwrap();
+        write(&dir, "Cargo.toml", "[package]\nname = \"demo\"\n");
+        write(&dir, ".gitignore", "generated/\n");
+        write(&dir, "generated/out.txt", "");
+        write(&dir, "docs/guide.md", "");
+        write(&dir, ".cline/context/build.md", "- Build with `make`\n");
+        
+        let content = ProjectContext::render(dir.path(), &dir.path().join(".cline/context"), 1).unwrap();
+        
+        assert!(content.contains("## Build\n\n- Build with `make`\n"));
+        assert!(!content.contains("cargo build"));
+        assert!(content.contains("docs/"));
+        assert!(!content.contains("generated/"));
+        assert!(!content.contains(".cline/"));
+    }
+}
@@ -0,0 +1,232 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::TempDir;
+use crate::error::{ClinerError, Result};
+use crate::models::{InitSource, InitSourceKind};
+
+/// A local copy of an `init --from` source: either the directory itself or a temporary git checkout.
+pub struct SourceCheckout {
+    pub source: InitSource,
+    root: PathBuf,
+    _temp_dir: Option<TempDir>,
+}
+
+impl SourceCheckout {
+    pub fn fetch(location: &str, reference: Option<&str>) -> Result<Self> {
+        // Anything starting with '-' would be read by git as an option.
+        if let Some(reference) = reference.filter(|reference| reference.starts_with('-')) {
+            return Err(ClinerError::InvalidFormat(format!("invalid git reference '{}'", reference)));
+        }
+        
+        let local_path = Path::new(location);
+        
+        if local_path.is_dir() && reference.is_none() {
+            return Ok(Self::from_directory(location, local_path));
+        }
+        
+        if !Self::is_git_location(location) && !local_path.is_dir() {
+            return Err(ClinerError::InvalidFormat(format!(
+                "'{}' is neither a directory nor a git repository URL",
+                location
+            )));
+        }
+        
+        Self::clone_git(location, reference)
+    }
+    
+    /// The directory holding `modes/` and `rules/`, inside `.cline` if the source is itself a project.
+    pub fn content_root(&self) -> PathBuf {
+        let cline_dir = self.root.join(".cline");
+        if cline_dir.is_dir() { cline_dir } else { self.root.clone() }
+    }
+    
+    pub fn is_git_location(location: &str) -> bool {
+        location.contains("://") || location.starts_with("git@") || location.ends_with(".git")
+    }
+    
+    fn from_directory(location: &str, path: &Path) -> Self {
+        let commit = Self::clean_commit(path);
+        let source = InitSource {
+            kind: InitSourceKind::Directory,
+            location: location.to_string(),
+            reference: None,
+            commit,
+        };
+        
+        Self { source, root: path.to_path_buf(), _temp_dir: None }
+    }
+    
+    /// The commit at `path`, if it is the top of a clean git checkout.
+    fn clean_commit(path: &Path) -> Option<String> {
+        let top_level = Self::git_output(path, &["rev-parse", "--show-toplevel"]).ok()?;
+        if Path::new(&top_level).canonicalize().ok()? != path.canonicalize().ok()? {
+            return None;
+        }
+        if !Self::git_output(path, &["status", "--porcelain"]).ok()?.is_empty() {
+            return None;
+        }
+        Self::git_output(path, &["rev-parse", "HEAD"]).ok()
+    }
+    
+    fn clone_git(location: &str, reference: Option<&str>) -> Result<Self> {
+        let temp_dir = tempfile::Builder::new().prefix("cliner-source-").tempdir()?;
+        let checkout_dir = temp_dir.path().join("checkout");
+        
+        let checkout_arg = checkout_dir.to_string_lossy().into_owned();
+        Self::run_git(None, &["clone", "--quiet", "--no-checkout", "--", location, &checkout_arg])?;
+        Self::run_git(Some(&checkout_dir), &["checkout", "--quiet", reference.unwrap_or("HEAD"), "--"])?;
+        let commit = Self::git_output(&checkout_dir, &["rev-parse", "HEAD"])?;
+        
+        let source = InitSource {
+            kind: InitSourceKind::Git,
+            location: location.to_string(),
+            reference: reference.map(String::from),
+            commit: Some(commit),
+        };
+        
+        Ok(Self { source, root: checkout_dir, _temp_dir: Some(temp_dir) })
+    }
+    
+    fn run_git(dir: Option<&Path>, args: &[&str]) -> Result<String> {
+        let mut command = Command::new("git");
+        if let Some(dir) = dir {
+            command.arg("-C").arg(dir);
+        }
+        
+        let output = command
+            .args(args)
+            .output()
+            .map_err(|err| ClinerError::Git(format!("failed to run git: {}", err)))?;
+        
+        if !output.status.success() {
+            return Err(ClinerError::Git(format!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+    
+    fn git_output(dir: &Path, args: &[&str]) -> Result<String> {
+        Self::run_git(Some(dir), args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["-c", "user.name=cliner", "-c", "user.email=cliner@example.com"])
+            .args(args)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    }
+    
+    fn create_rules_repo() -> TempDir {
+        let repo_dir = TempDir::new().unwrap();
+        fs::create_dir_all(repo_dir.path().join("rules")).unwrap();
+        fs::write(repo_dir.path().join("rules").join("00_style.md"), "v1").unwrap();
+        git(repo_dir.path(), &["init", "--quiet"]);
+        git(repo_dir.path(), &["add", "."]);
+        git(repo_dir.path(), &["commit", "--quiet", "-m", "v1"]);
+        git(repo_dir.path(), &["tag", "v1"]);
+        
+        fs::write(repo_dir.path().join("rules").join("00_style.md"), "v2").unwrap();
+        git(repo_dir.path(), &["commit", "--quiet", "-am", "v2"]);
+        repo_dir
+    }
+    
+    #[test]
+    fn test_is_git_location() {
+        assert!(SourceCheckout::is_git_location("https://example.com/rules.git"));
+        assert!(SourceCheckout::is_git_location("file:///srv/rules"));
+        assert!(SourceCheckout::is_git_location("git@example.com:org/rules"));
+        assert!(!SourceCheckout::is_git_location("../rules"));
+    }
+    
+    #[test]
+    fn test_fetch_directory() {
+        let source_dir = TempDir::new().unwrap();
+        fs::create_dir_all(source_dir.path().join(".cline").join("rules")).unwrap();
+        let location = source_dir.path().to_str().unwrap();
+        
+        let checkout = SourceCheckout::fetch(location, None).unwrap();
+        
+        assert_eq!(checkout.source.kind, InitSourceKind::Directory);
+        assert_eq!(checkout.content_root(), source_dir.path().join(".cline"));
+    }
+    
+    #[test]
+    fn test_fetch_file_url_at_ref() {
+        let repo_dir = create_rules_repo();
+        let url = format!("file://{}", repo_dir.path().display());
+        
+        let checkout = SourceCheckout::fetch(&url, Some("v1")).unwrap();
+        
+        assert_eq!(checkout.source.kind, InitSourceKind::Git);
+        assert_eq!(checkout.source.reference.as_deref(), Some("v1"));
+        assert_eq!(checkout.source.commit.as_ref().map(String::len), Some(40));
+        let content = fs::read_to_string(checkout.content_root().join("rules").join("00_style.md")).unwrap();
+        assert_eq!(content, "v1");
+        
+        let latest = SourceCheckout::fetch(&url, None).unwrap();
+        let content = fs::read_to_string(latest.content_root().join("rules").join("00_style.md")).unwrap();
+        assert_eq!(content, "v2");
+        assert_ne!(latest.source.commit, checkout.source.commit);
+    }
+    
+    #[test]
+    fn test_fetch_unknown_ref_fails() {
+        let repo_dir = create_rules_repo();
+        let url = format!("file://{}", repo_dir.path().display());
+        
+        let result = SourceCheckout::fetch(&url, Some("does-not-exist"));
+        
+        assert!(matches!(result, Err(ClinerError::Git(_))));
+    }
+    
+    #[test]
+    fn test_fetch_directory_records_only_clean_top_level_commit() {
+        let repo_dir = create_rules_repo();
+        let location = repo_dir.path().to_str().unwrap();
+        
+        let clean = SourceCheckout::fetch(location, None).unwrap();
+        assert_eq!(clean.source.commit.as_ref().map(String::len), Some(40));
+        
+        let subdirectory = repo_dir.path().join("rules");
+        let nested = SourceCheckout::fetch(subdirectory.to_str().unwrap(), None).unwrap();
+        assert_eq!(nested.source.commit, None);
+        
+        fs::write(repo_dir.path().join("rules").join("00_style.md"), "edited").unwrap();
+        let dirty = SourceCheckout::fetch(location, None).unwrap();
+        assert_eq!(dirty.source.commit, None);
+    }
+    
+    #[test]
+    fn test_fetch_rejects_option_like_arguments() {
+        let repo_dir = create_rules_repo();
+        let url = format!("file://{}", repo_dir.path().display());
+        let marker = repo_dir.path().join("pwned");
+        
+        let result = SourceCheckout::fetch(&url, Some("--orphan=x"));
+        assert!(matches!(result, Err(ClinerError::InvalidFormat(_))));
+        
+        let location = format!("--upload-pack=touch {}; false x.git", marker.display());
+        assert!(SourceCheckout::fetch(&location, None).is_err());
+        assert!(!marker.exists());
+    }
+    
+    #[test]
+    fn test_fetch_missing_path_fails() {
+        assert!(SourceCheckout::fetch("/definitely/not/here", None).is_err());
+    }
+}
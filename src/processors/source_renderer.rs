@@ -0,0 +1,396 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+use crate::error::{ClinerError, Result};
+use crate::models::{ClinePaths, Condition, GenerateOptions, OutputTarget, UndefinedPolicy};
+use crate::processors::{CodeFence, CommandRunner, FileExcerpt, IncludeResolver};
+use crate::processors::include_resolver::parse_comment_directive;
+use crate::processors::three_way_merge::CONFLICT_START;
+
+/// Drops inactive `@if` blocks, expands includes and replaces `{{...}}` placeholders in every rule and mode.
+#[derive(Debug, Default)]
+pub struct SourceRenderer {
+    includes: IncludeResolver,
+    variables: BTreeMap<String, String>,
+    undefined: UndefinedPolicy,
+    profile: Option<String>,
+    commands: CommandRunner,
+    root: PathBuf,
+    git_branch: OnceLock<Option<String>>,
+}
+
+impl SourceRenderer {
+    pub fn new(paths: &ClinePaths, options: &GenerateOptions) -> Self {
+        let mut variables = BTreeMap::new();
+        let root = paths.root.canonicalize().unwrap_or_else(|_| paths.root.clone());
+        if let Some(name) = root.file_name() {
+            variables.insert("project.name".to_string(), name.to_string_lossy().into_owned());
+        }
+        variables.insert("project.root".to_string(), root.display().to_string());
+        variables.extend(options.variables.clone());
+        
+        Self {
+            includes: IncludeResolver::new(&paths.include_roots()),
+            variables,
+            undefined: options.undefined,
+            profile: options.profile.clone(),
+            commands: CommandRunner::new(options.commands.clone(), root.clone(), paths.command_cache_dir(), options.cached_commands),
+            root,
+            git_branch: OnceLock::new(),
+        }
+    }
+    
+    pub fn render(&self, content: &str, path: &Path, target: OutputTarget) -> Result<String> {
+        self.includes.resolve(content, path, &|text, path| self.render_file(text, path, target))
+    }
+    
+    /// Drops inactive `@if` blocks and interpolates the remaining lines, keeping their line numbers.
+    fn render_file(&self, content: &str, path: &Path, target: OutputTarget) -> Result<Vec<(usize, String)>> {
+        let mut rendered = Vec::new();
+        let mut blocks: Vec<ConditionalBlock> = Vec::new();
+        let value_of = |name: &str| match name {
+            "target" => Some(target.as_str().to_string()),
+            "profile" => self.profile.clone(),
+            name => self.lookup(name),
+        };
+        
+        let mut fence = CodeFence::default();
+        for (index, line) in content.split_inclusive('\n').enumerate() {
+            let error = |message: String| self.includes.error_at(path, index + 1, message);
+            let active = blocks.last().is_none_or(ConditionalBlock::is_active);
+            let in_fence = fence.update(line);
+            if line.trim_end() == CONFLICT_START {
+                return Err(error("unresolved conflict from `cliner update`; resolve it before generating".to_string()));
+            }
+            
+            if in_fence {
+                if active {
+                    rendered.push((index + 1, self.interpolate(line, true).map_err(error)?));
+                }
+            } else if let Some(expression) = parse_comment_directive(line, "@if") {
+                let condition = Condition::parse(expression).map_err(|err| match err {
+                    ClinerError::InvalidFormat(message) => error(message),
+                    err => err,
+                })?;
+                let matched = active && condition.evaluate(&value_of);
+                blocks.push(ConditionalBlock { line: index + 1, parent_active: active, matched, in_else: false });
+            } else if let Some(argument) = parse_comment_directive(line, "@else") {
+                match blocks.last_mut() {
+                    Some(block) if !block.in_else && argument.is_empty() => block.in_else = true,
+                    Some(_) if argument.is_empty() => return Err(error("second '@else' in one '@if' block".to_string())),
+                    Some(_) => return Err(error(format!("unexpected '{}' after '@else'", argument))),
+                    None => return Err(error("'@else' without '@if'".to_string())),
+                }
+            } else if parse_comment_directive(line, "@endif").is_some() {
+                if blocks.pop().is_none() {
+                    return Err(error("'@endif' without '@if'".to_string()));
+                }
+            } else if active {
+                rendered.push((index + 1, self.interpolate(line, false).map_err(error)?));
+            }
+        }
+        
+        if let Some(block) = blocks.last() {
+            return Err(self.includes.error_at(path, block.line, "'@if' without '@endif'".to_string()));
+        }
+        Ok(rendered)
+    }
+    
+    /// Replaces the placeholders in one line; in a code block, anything cliner can't resolve is left as written.
+    fn interpolate(&self, line: &str, in_fence: bool) -> std::result::Result<String, String> {
+        let lenient = in_fence || self.undefined != UndefinedPolicy::Error;
+        let mut rendered = String::with_capacity(line.len());
+        let mut rest = line;
+        
+        while let Some(start) = rest.find("{{") {
+            if rest[..start].ends_with('\\') {
+                rendered.push_str(&rest[..start - 1]);
+                rendered.push_str("{{");
+                rest = &rest[start + 2..];
+                continue;
+            }
+            
+            rendered.push_str(&rest[..start]);
+            let Some(length) = rest[start + 2..].find("}}") else {
+                if lenient {
+                    rest = &rest[start..];
+                    break;
+                }
+                return Err("unclosed '{{'; write '\\{{' for a literal '{{'".to_string());
+            };
+            let placeholder = &rest[start..start + length + 4];
+            let name = placeholder[2..placeholder.len() - 2].trim();
+            rest = &rest[start + length + 4..];
+            
+            if let Some(reference) = name.strip_prefix("file:") {
+                rendered.push_str(&FileExcerpt::parse(reference)?.read(&self.root)?);
+                continue;
+            }
+            if let Some(command) = name.strip_prefix("command:") {
+                rendered.push_str(&self.commands.output(command.trim())?);
+                continue;
+            }
+            if !Self::is_variable_name(name) {
+                if lenient {
+                    rendered.push_str(placeholder);
+                    continue;
+                }
+                return Err(format!("invalid variable name '{}'", name));
+            }
+            
+            match self.lookup(name) {
+                Some(value) => rendered.push_str(&value),
+                None if in_fence => rendered.push_str(placeholder),
+                None => match self.undefined {
+                    UndefinedPolicy::Error => return Err(format!("undefined variable '{}'", name)),
+                    UndefinedPolicy::Empty => {},
+                    UndefinedPolicy::Keep => rendered.push_str(placeholder),
+                },
+            }
+        }
+        
+        rendered.push_str(rest);
+        Ok(rendered)
+    }
+    
+    fn is_variable_name(name: &str) -> bool {
+        !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'))
+    }
+    
+    fn lookup(&self, name: &str) -> Option<String> {
+        if let Some(value) = self.variables.get(name) {
+            return Some(value.clone());
+        }
+        if let Some(variable) = name.strip_prefix("env.") {
+            return env::var(variable).ok();
+        }
+        if name == "git.branch" {
+            return self.git_branch.get_or_init(|| Self::current_branch(&self.root)).clone();
+        }
+        
+        None
+    }
+    
+    fn current_branch(root: &Path) -> Option<String> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(root)
+            .args(["rev-parse", "--abbrev-ref", "HEAD"])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        
+        let branch = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        Some(branch).filter(|branch| !branch.is_empty())
+    }
+}
+
+/// An open `@if` block while rendering a file.
+struct ConditionalBlock {
+    line: usize,
+    parent_active: bool,
+    matched: bool,
+    in_else: bool,
+}
+
+impl ConditionalBlock {
+    fn is_active(&self) -> bool {
+        self.parent_active && self.matched != self.in_else
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+    
+    fn renderer(variables: &[(&str, &str)], undefined: UndefinedPolicy) -> SourceRenderer {
+        let options = GenerateOptions {
+            variables: variables.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect(),
+            undefined,
+            ..GenerateOptions::default()
+        };
+        SourceRenderer::new(&ClinePaths::from_root("/projects/acme"), &options)
+    }
+    
+    #[test]
+    fn test_interpolate_variables() {
+        let renderer = renderer(&[("team", "platform")], UndefinedPolicy::Error);
+        
+        let rendered = renderer.render("{{project.name}} is owned by {{ team }}.\n", Path::new("00.md"), OutputTarget::Clinerules).unwrap();
+        
+        assert_eq!(rendered, "acme is owned by platform.\n");
+    }
+    
+    #[test]
+    fn test_env_variables() {
+        let renderer = renderer(&[], UndefinedPolicy::Error);
+        
+        let rendered = renderer.render("{{env.PATH}}", Path::new("00.md"), OutputTarget::Clinerules).unwrap();
+        
+        assert_eq!(rendered, env::var("PATH").unwrap());
+    }
+    
+    #[test]
+    fn test_config_overrides_builtins() {
+        let renderer = renderer(&[("project.name", "Acme Corp")], UndefinedPolicy::Error);
+        
+        assert_eq!(renderer.render("{{project.name}}", Path::new("00.md"), OutputTarget::Clinerules).unwrap(), "Acme Corp");
+    }
+    
+    #[test]
+    fn test_conflict_markers_are_rejected() {
+        let renderer = renderer(&[], UndefinedPolicy::Keep);
+        let content = "# Style\n<<<<<<< local\nUse tabs\n=======\nUse spaces\n>>>>>>> upstream\n";
+        
+        let message = renderer.render(content, Path::new("00.md"), OutputTarget::Clinerules).unwrap_err().to_string();
+        
+        assert!(message.contains("00.md:2: unresolved conflict"), "{}", message);
+    }
+    
+    #[test]
+    fn test_escaped_braces() {
+        let renderer = renderer(&[], UndefinedPolicy::Error);
+        
+        let rendered = renderer.render("Use \\{{ name }} in templates", Path::new("00.md"), OutputTarget::Clinerules).unwrap();
+        
+        assert_eq!(rendered, "Use {{ name }} in templates");
+    }
+    
+    #[test]
+    fn test_undefined_variables() {
+        let content = "# Rule\nHello {{missing}}!\n";
+        
+        let message = renderer(&[], UndefinedPolicy::Error).render(content, Path::new("00.md"), OutputTarget::Clinerules).unwrap_err().to_string();
+        assert!(message.contains("00.md:2: undefined variable 'missing'"), "{}", message);
+        
+        let empty = renderer(&[], UndefinedPolicy::Empty).render(content, Path::new("00.md"), OutputTarget::Clinerules).unwrap();
+        assert_eq!(empty, "# Rule\nHello !\n");
+        
+        let kept = renderer(&[], UndefinedPolicy::Keep).render(content, Path::new("00.md"), OutputTarget::Clinerules).unwrap();
+        assert_eq!(kept, content);
+    }
+    
+    #[test]
+    fn test_syntax_errors() {
+        let renderer = renderer(&[], UndefinedPolicy::Error);
+        
+        let unclosed = renderer.render("a {{ b", Path::new("00.md"), OutputTarget::Clinerules).unwrap_err().to_string();
+        assert!(unclosed.contains("00.md:1: unclosed '{{'"), "{}", unclosed);
+        
+        let invalid = renderer.render("{{#each items}}", Path::new("00.md"), OutputTarget::Clinerules).unwrap_err().to_string();
+        assert!(invalid.contains("invalid variable name '#each items'"), "{}", invalid);
+    }
+    
+    #[test]
+    fn test_foreign_placeholders_are_kept_when_lenient() {
+        let content = "{{#each items}} {{team}} {{ a\n";
+        
+        assert_eq!(renderer(&[("team", "core")], UndefinedPolicy::Keep).render(content, Path::new("00.md"), OutputTarget::Clinerules).unwrap(), "{{#each items}} core {{ a\n");
+        assert_eq!(renderer(&[], UndefinedPolicy::Empty).render(content, Path::new("00.md"), OutputTarget::Clinerules).unwrap(), "{{#each items}}  {{ a\n");
+    }
+    
+    #[test]
+    fn test_code_blocks_keep_github_actions_expressions() {
+        let renderer = renderer(&[("team", "core")], UndefinedPolicy::Error);
+        let content = "\
+Owned by {{team}}.
+
+```yaml
+- run: docker build -t app:${{ github.sha }} .
+  if: ${{ !cancelled() }}
+  env:
+    TEAM: {{team}}
+```
+";
+        
+        let rendered = renderer.render(content, Path::new("00.md"), OutputTarget::Clinerules).unwrap();
+        
+        assert_eq!(rendered, content.replace("{{team}}", "core"));
+    }
+    
+    #[test]
+    fn test_included_files_are_rendered() {
+        let dir = tempdir().unwrap();
+        let cline_dir = dir.path().join(".cline");
+        fs::create_dir_all(cline_dir.join("rules")).unwrap();
+        fs::write(cline_dir.join("partial.md"), "Team {{team}}\n").unwrap();
+        let options = GenerateOptions {
+            variables: BTreeMap::from([("team".to_string(), "platform".to_string())]),
+            ..GenerateOptions::default()
+        };
+        let renderer = SourceRenderer::new(&ClinePaths::from_root(dir.path()), &options);
+        
+        let rendered = renderer.render("<!-- @include ../partial.md -->\n", &cline_dir.join("rules/00.md"), OutputTarget::Clinerules).unwrap();
+        
+        assert_eq!(rendered, "Team platform\n");
+    }
+    
+    #[test]
+    fn test_conditional_blocks() {
+        let options = GenerateOptions { profile: Some("ci".to_string()), ..GenerateOptions::default() };
+        let renderer = SourceRenderer::new(&ClinePaths::from_root("/projects/acme"), &options);
+        let content = "\
+Always
+<!-- @if target == \"cursor\" -->
+Press Cmd+K {{undefined}}
+<!-- @else -->
+Use the command palette
+<!-- @if profile == \"ci\" && project.name == \"acme\" -->
+In CI
+<!-- @endif -->
+<!-- @endif -->
+";
+        
+        let rendered = renderer.render(content, Path::new("00.md"), OutputTarget::Clinerules).unwrap();
+        
+        assert_eq!(rendered, "Always\nUse the command palette\nIn CI\n");
+    }
+    
+    #[test]
+    fn test_unbalanced_conditional_blocks() {
+        let renderer = renderer(&[], UndefinedPolicy::Error);
+        let render = |content: &str| renderer.render(content, Path::new("00.md"), OutputTarget::Roomodes).unwrap_err().to_string();
+        
+        assert!(render("a\n<!-- @if profile -->\nb\n").contains("00.md:2: '@if' without '@endif'"));
+        assert!(render("<!-- @endif -->\n").contains("00.md:1: '@endif' without '@if'"));
+        assert!(render("<!-- @if target = \"x\" -->\n").contains("00.md:1: invalid condition 'target = \"x\"': expected '==' at column 8"));
+    }
+    
+    #[test]
+    fn test_include_errors_below_conditional_blocks_name_the_source_line() {
+        let dir = tempdir().unwrap();
+        let cline_dir = dir.path().join(".cline");
+        fs::create_dir_all(cline_dir.join("rules")).unwrap();
+        let renderer = SourceRenderer::new(&ClinePaths::from_root(dir.path()), &GenerateOptions::default());
+        let content = "\
+# Rules
+<!-- @if profile == \"ci\" -->
+Only in CI
+<!-- @endif -->
+<!-- @include missing.md -->
+";
+        
+        let message = renderer.render(content, &cline_dir.join("rules/00.md"), OutputTarget::Clinerules).unwrap_err().to_string();
+        
+        assert!(message.contains("00.md:5: included file 'missing.md' not found"), "{}", message);
+    }
+    
+    #[test]
+    fn test_file_excerpts() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("schema.graphql"), "type Query {\n  user: User\n}\n").unwrap();
+        let renderer = SourceRenderer::new(&ClinePaths::from_root(dir.path()), &GenerateOptions::default());
+        
+        let rendered = renderer.render("```graphql\n{{file:schema.graphql#L1-2}}\n```\n", Path::new("00.md"), OutputTarget::Clinerules).unwrap();
+        assert_eq!(rendered, "```graphql\ntype Query {\n  user: User\n```\n");
+        
+        let message = renderer.render("{{file:missing.graphql}}", Path::new("00.md"), OutputTarget::Clinerules).unwrap_err().to_string();
+        assert!(message.contains("00.md:1: embedded file 'missing.graphql' not found"), "{}", message);
+    }
+}
@@ -0,0 +1,189 @@
+pub const CONFLICT_START: &str = "<<<<<<< local";
+pub const CONFLICT_SEPARATOR: &str = "=======";
+pub const CONFLICT_END: &str = ">>>>>>> upstream";
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct MergeResult {
+    pub content: String,
+    pub conflicts: usize,
+}
+
+/// Line-based three-way merge in the style of diff3; conflicts list the local lines first.
+pub struct ThreeWayMerge;
+
+impl ThreeWayMerge {
+    pub fn merge(base: &str, local: &str, upstream: &str) -> MergeResult {
+        let base_lines: Vec<&str> = base.split_inclusive('\n').collect();
+        let local_lines: Vec<&str> = local.split_inclusive('\n').collect();
+        let upstream_lines: Vec<&str> = upstream.split_inclusive('\n').collect();
+        
+        let local_matches = Self::match_lines(&base_lines, &local_lines);
+        let upstream_matches = Self::match_lines(&base_lines, &upstream_lines);
+        
+        let mut content = String::new();
+        let mut conflicts = 0;
+        let (mut base_index, mut local_index, mut upstream_index) = (0, 0, 0);
+        
+        loop {
+            let anchor = (base_index..base_lines.len()).find_map(|index| {
+                match (local_matches[index], upstream_matches[index]) {
+                    (Some(local_match), Some(upstream_match))
+                        if local_match >= local_index && upstream_match >= upstream_index =>
+                    {
+                        Some((index, local_match, upstream_match))
+                    },
+                    _ => None,
+                }
+            });
+            
+            let (base_end, local_end, upstream_end) = anchor.unwrap_or((base_lines.len(), local_lines.len(), upstream_lines.len()));
+            
+            let hunk_conflicts = Self::merge_hunk(
+                &base_lines[base_index..base_end],
+                &local_lines[local_index..local_end],
+                &upstream_lines[upstream_index..upstream_end],
+                &mut content,
+            );
+            conflicts += hunk_conflicts;
+            
+            match anchor {
+                Some(_) => {
+                    content.push_str(base_lines[base_end]);
+                    base_index = base_end + 1;
+                    local_index = local_end + 1;
+                    upstream_index = upstream_end + 1;
+                },
+                None => break,
+            }
+        }
+        
+        MergeResult { content, conflicts }
+    }
+    
+    fn merge_hunk(base: &[&str], local: &[&str], upstream: &[&str], content: &mut String) -> usize {
+        if local == base || local == upstream {
+            content.push_str(&upstream.concat());
+            return 0;
+        }
+        
+        if upstream == base {
+            content.push_str(&local.concat());
+            return 0;
+        }
+        
+        content.push_str(CONFLICT_START);
+        content.push('\n');
+        Self::push_block(content, local);
+        content.push_str(CONFLICT_SEPARATOR);
+        content.push('\n');
+        Self::push_block(content, upstream);
+        content.push_str(CONFLICT_END);
+        content.push('\n');
+        1
+    }
+    
+    fn push_block(content: &mut String, lines: &[&str]) {
+        let block = lines.concat();
+        content.push_str(&block);
+        if !block.is_empty() && !block.ends_with('\n') {
+            content.push('\n');
+        }
+    }
+    
+    /// For every line of `base`, the index of the matching line in `other` on a longest common subsequence.
+    fn match_lines(base: &[&str], other: &[&str]) -> Vec<Option<usize>> {
+        let mut lengths = vec![vec![0usize; other.len() + 1]; base.len() + 1];
+        for base_index in (0..base.len()).rev() {
+            for other_index in (0..other.len()).rev() {
+                lengths[base_index][other_index] = if base[base_index] == other[other_index] {
+                    lengths[base_index + 1][other_index + 1] + 1
+                } else {
+                    lengths[base_index + 1][other_index].max(lengths[base_index][other_index + 1])
+                };
+            }
+        }
+        
+        let mut matches = vec![None; base.len()];
+        let (mut base_index, mut other_index) = (0, 0);
+        while base_index < base.len() && other_index < other.len() {
+            if base[base_index] == other[other_index] {
+                matches[base_index] = Some(other_index);
+                base_index += 1;
+                other_index += 1;
+            } else if lengths[base_index + 1][other_index] >= lengths[base_index][other_index + 1] {
+                base_index += 1;
+            } else {
+                other_index += 1;
+            }
+        }
+        
+        matches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_upstream_only_change_applies_cleanly() {
+        let base = "a\nb\nc\n";
+        let local = "a\nb\nc\n";
+        let upstream = "a\nB\nc\n";
+        
+        let result = ThreeWayMerge::merge(base, local, upstream);
+        
+        assert_eq!(result, MergeResult { content: "a\nB\nc\n".to_string(), conflicts: 0 });
+    }
+    
+    #[test]
+    fn test_non_overlapping_changes_combine() {
+        let base = "# Title\n\nintro\n\n- one\n- two\n";
+        let local = "# Title\n\nintro edited locally\n\n- one\n- two\n";
+        let upstream = "# Title\n\nintro\n\n- one\n- two\n- three\n";
+        
+        let result = ThreeWayMerge::merge(base, local, upstream);
+        
+        assert_eq!(result.conflicts, 0);
+        assert_eq!(result.content, "# Title\n\nintro edited locally\n\n- one\n- two\n- three\n");
+    }
+    
+    #[test]
+    fn test_identical_changes_do_not_conflict() {
+        let result = ThreeWayMerge::merge("a\nb\n", "a\nx\n", "a\nx\n");
+        
+        assert_eq!(result, MergeResult { content: "a\nx\n".to_string(), conflicts: 0 });
+    }
+    
+    #[test]
+    fn test_overlapping_changes_conflict() {
+        let base = "a\nb\nc\n";
+        let local = "a\nlocal\nc\n";
+        let upstream = "a\nupstream\nc\n";
+        
+        let result = ThreeWayMerge::merge(base, local, upstream);
+        
+        assert_eq!(result.conflicts, 1);
+        assert_eq!(
+            result.content,
+            "a\n<<<<<<< local\nlocal\n=======\nupstream\n>>>>>>> upstream\nc\n"
+        );
+    }
+    
+    #[test]
+    fn test_missing_trailing_newline_in_conflict() {
+        let result = ThreeWayMerge::merge("a\nb", "a\nlocal", "a\nupstream");
+        
+        assert_eq!(result.conflicts, 1);
+        assert!(result.content.ends_with("local\n=======\nupstream\n>>>>>>> upstream\n"));
+    }
+    
+    #[test]
+    fn test_empty_base() {
+        let result = ThreeWayMerge::merge("", "local\n", "upstream\n");
+        assert_eq!(result.conflicts, 1);
+        
+        let result = ThreeWayMerge::merge("", "same\n", "same\n");
+        assert_eq!(result, MergeResult { content: "same\n".to_string(), conflicts: 0 });
+    }
+}
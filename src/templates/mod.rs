@@ -0,0 +1,88 @@
+use crate::models::SourceKind;
+
+pub struct TemplateFile {
+    pub kind: SourceKind,
+    pub file_name: &'static str,
+    pub content: &'static str,
+}
+
+pub struct Template {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub files: &'static [TemplateFile],
+}
+
+macro_rules! template_file {
+    ($kind:expr, $template:literal, $dir:literal, $file_name:literal) => {
+        TemplateFile {
+            kind: $kind,
+            file_name: $file_name,
+            content: include_str!(concat!("../../templates/", $template, "/", $dir, "/", $file_name)),
+        }
+    };
+}
+
+pub const TEMPLATES: &[Template] = &[
+    Template {
+        name: "rust",
+        description: "Rust crate or workspace: reviewer mode, Rust conventions and cargo workflow",
+        files: &[
+            template_file!(SourceKind::Modes, "rust", "modes", "00_rust-reviewer.md"),
+            template_file!(SourceKind::Rules, "rust", "rules", "00_rust.md"),
+            template_file!(SourceKind::Rules, "rust", "rules", "01_workflow.md"),
+        ],
+    },
+    Template {
+        name: "web",
+        description: "Web app: frontend mode, accessibility and web conventions",
+        files: &[
+            template_file!(SourceKind::Modes, "web", "modes", "00_frontend.md"),
+            template_file!(SourceKind::Rules, "web", "rules", "00_web.md"),
+            template_file!(SourceKind::Rules, "web", "rules", "01_workflow.md"),
+        ],
+    },
+    Template {
+        name: "docs",
+        description: "Documentation repository: technical writer mode and docs conventions",
+        files: &[
+            template_file!(SourceKind::Modes, "docs", "modes", "00_technical-writer.md"),
+            template_file!(SourceKind::Rules, "docs", "rules", "00_docs.md"),
+        ],
+    },
+];
+
+pub const TEMPLATE_NAMES: [&str; 3] = ["rust", "web", "docs"];
+
+pub fn find_template(name: &str) -> Option<&'static Template> {
+    TEMPLATES.iter().find(|template| template.name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processors::markdown_parser::MarkdownParser;
+    
+    #[test]
+    fn test_template_names_match_registry() {
+        let names: Vec<&str> = TEMPLATES.iter().map(|template| template.name).collect();
+        assert_eq!(names, TEMPLATE_NAMES.to_vec());
+    }
+    
+    #[test]
+    fn test_find_template() {
+        assert_eq!(find_template("rust").unwrap().name, "rust");
+        assert!(find_template("cobol").is_none());
+    }
+    
+    #[test]
+    fn test_template_modes_parse() {
+        for template in TEMPLATES {
+            for file in template.files.iter().filter(|file| file.kind == SourceKind::Modes) {
+                let mode = MarkdownParser::parse_to_mode(file.content);
+                assert!(mode.is_ok(), "{}/{} should be a valid mode", template.name, file.file_name);
+            }
+            
+            assert!(template.files.iter().any(|file| file.kind == SourceKind::Rules));
+        }
+    }
+}